@@ -1,14 +1,27 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::GaugeInput;
 use crate::{
-    AnovaTableView, DataTableView, EXAMPLE_CSV, EXAMPLE_JSON, GageEvalTableView, PlotType,
-    StudyPlots, VarCompTableView,
+    AnovaTableView, AttributeAgreement, AttributeAgreementTableView, AttributeData,
+    AttributeDataTableView, ChartType, DataEntry, DataEntryEvent, DataTableView,
+    EXAMPLE_ATTRIBUTE_CSV, EXAMPLE_CSV, EXAMPLE_JSON, EXAMPLE_STABILITY_CSV, ExpandedAnova,
+    ExpandedAnovaTableView, ExpandedData, FactorSpec, GageEvalTableView, LinearityPlot,
+    LinearityStudy, LinearityTableView, NestedAnova, NestedAnovaTableView, OutlierScreen, PlotType,
+    Reml, ResidualPlots, Residuals, ResolutionCheck, RuleSet, Simulator, SpecLimits,
+    StabilityChart, StabilityData, StabilityStudy, StabilityTableView, StudyPlanner, StudyPlots,
+    Type1RunChart, Type1Study, Type1TableView, VarCompTableView, VarianceComponents,
+    WorksheetGenerator,
 };
 use eframe::egui::{self, Color32, RichText};
 use gage_study::{anova::Anova, data::Data, dataset::DataSet, study_evaluation::StudyEvaluation};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, VecDeque};
 use std::path::Path;
 
 pub enum Message {
     FileOpen(FileInfo),
+    AttributeFileOpen(AttributeFileInfo),
+    ExpandedFileOpen(ExpandedFileInfo),
+    StabilityFileOpen(StabilityFileInfo),
     #[allow(dead_code)]
     LogFile(Vec<u8>),
 }
@@ -18,19 +31,126 @@ pub struct FileInfo {
     pub content: Vec<Data>,
 }
 
+pub struct AttributeFileInfo {
+    pub name: String,
+    pub content: Vec<AttributeData>,
+}
+
+pub struct ExpandedFileInfo {
+    pub name: String,
+    pub content: Vec<ExpandedData>,
+}
+
+pub struct StabilityFileInfo {
+    pub name: String,
+    pub content: Vec<StabilityData>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum StudyType {
+    Crossed,
+    Nested,
+    Expanded,
+    Type1,
+    Linearity,
+    Attribute,
+    Stability,
+}
+
+impl StudyType {
+    pub const ALL: [StudyType; 7] = [
+        StudyType::Crossed,
+        StudyType::Nested,
+        StudyType::Expanded,
+        StudyType::Type1,
+        StudyType::Linearity,
+        StudyType::Attribute,
+        StudyType::Stability,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StudyType::Crossed => "Crossed Gage R&R",
+            StudyType::Nested => "Nested Gage R&R",
+            StudyType::Expanded => "Expanded Gage R&R",
+            StudyType::Type1 => "Type 1 (Cg/Cgk)",
+            StudyType::Linearity => "Linearity and Bias",
+            StudyType::Attribute => "Attribute Agreement",
+            StudyType::Stability => "Stability (Control Charts)",
+        }
+    }
+}
+
 // if we add new fields, give them default values when deserializing old state
 #[derive(Deserialize, Serialize)]
 #[serde(default)]
 pub struct GageStudyApp {
     label: String,
     dataset: Vec<Data>,
+    attribute_dataset: Vec<AttributeData>,
+    expanded_dataset: Vec<ExpandedData>,
+    stability_dataset: Vec<StabilityData>,
     concatenate_data: bool,
     tolerance: f64,
+    use_spec_limits: bool,
+    lsl: f64,
+    usl: f64,
+    units: String,
     process_variation: f64,
+    use_historical_stddev: bool,
+    historical_stddev: f64,
+    use_resolution: bool,
+    resolution: f64,
     refresh_plot: bool,
+    study_type: StudyType,
+    type1_k_percent: f64,
+    type1_spread: f64,
+    linearity_process_variation: f64,
+    attribute_pass_rating: String,
+    expanded_factors: Vec<FactorSpec>,
+    expanded_interactions: bool,
+    stability_chart_type: ChartType,
+    stability_subgroup_size: usize,
+    stability_rule_set: RuleSet,
+    planner: StudyPlanner,
+    simulator: Simulator,
+    worksheet_generator: WorksheetGenerator,
+    #[serde(skip)]
+    show_planner: bool,
+    #[serde(skip)]
+    show_simulator: bool,
+    #[serde(skip)]
+    show_worksheet_generator: bool,
+    #[serde(skip)]
+    data_entry: DataEntry,
+    #[serde(skip)]
+    show_data_entry: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    gauge_input: GaugeInput,
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    show_gauge_input: bool,
+    /// Gauge readings not yet submitted to guided data entry
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    gauge_queue: VecDeque<f64>,
     #[serde(skip)]
     gage_dataset: Option<DataSet>,
     #[serde(skip)]
+    plot_data: Vec<Data>,
+    #[serde(skip)]
+    residuals: Option<Residuals>,
+    #[serde(skip)]
+    outliers: Option<OutlierScreen>,
+    /// Rows of `dataset` left out of the crossed study, kept so they can be restored
+    #[serde(skip)]
+    excluded_rows: BTreeSet<usize>,
+    /// Rows of `plot_data` selected from a plot
+    #[serde(skip)]
+    selected_rows: Vec<usize>,
+    #[serde(skip)]
+    scroll_to_selection: bool,
+    #[serde(skip)]
     message_channel: (
         std::sync::mpsc::Sender<Message>,
         std::sync::mpsc::Receiver<Message>,
@@ -42,6 +162,22 @@ pub struct GageStudyApp {
     #[serde(skip)]
     study_evaluation: Option<StudyEvaluation>,
     #[serde(skip)]
+    nested_anova: Option<NestedAnova>,
+    #[serde(skip)]
+    expanded_anova: Option<ExpandedAnova>,
+    #[serde(skip)]
+    variance_components: Option<VarianceComponents>,
+    #[serde(skip)]
+    type1_study: Option<Type1Study>,
+    #[serde(skip)]
+    linearity_study: Option<LinearityStudy>,
+    #[serde(skip)]
+    attribute_agreement: Option<AttributeAgreement>,
+    #[serde(skip)]
+    stability_study: Option<StabilityStudy>,
+    #[serde(skip)]
+    study_error: Option<String>,
+    #[serde(skip)]
     msg: Vec<u8>,
 }
 
@@ -50,15 +186,64 @@ impl Default for GageStudyApp {
         Self {
             label: "Gage Study Analysis".to_owned(),
             dataset: Vec::new(),
+            attribute_dataset: Vec::new(),
+            expanded_dataset: Vec::new(),
+            stability_dataset: Vec::new(),
             gage_dataset: None,
+            plot_data: Vec::new(),
+            residuals: None,
+            outliers: None,
+            excluded_rows: BTreeSet::new(),
+            selected_rows: Vec::new(),
+            scroll_to_selection: false,
             message_channel: std::sync::mpsc::channel(),
             concatenate_data: true,
             tolerance: 1.0,
+            use_spec_limits: false,
+            lsl: 0.0,
+            usl: 1.0,
+            units: String::new(),
             process_variation: 5.15,
+            use_historical_stddev: false,
+            historical_stddev: 1.0,
+            use_resolution: false,
+            resolution: 0.001,
             refresh_plot: false,
+            study_type: StudyType::Crossed,
+            type1_k_percent: 20.0,
+            type1_spread: 6.0,
+            linearity_process_variation: 1.0,
+            attribute_pass_rating: "pass".to_owned(),
+            expanded_factors: vec![FactorSpec::new("Part"), FactorSpec::new("Operator")],
+            expanded_interactions: true,
+            stability_chart_type: ChartType::IndividualsMovingRange,
+            stability_subgroup_size: 5,
+            stability_rule_set: RuleSet::Nelson,
+            planner: StudyPlanner::default(),
+            simulator: Simulator::default(),
+            worksheet_generator: WorksheetGenerator::default(),
+            show_planner: false,
+            show_simulator: false,
+            show_worksheet_generator: false,
+            data_entry: DataEntry::default(),
+            show_data_entry: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            gauge_input: GaugeInput::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            show_gauge_input: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            gauge_queue: VecDeque::new(),
             open_files: Vec::new(),
             anova: None,
             study_evaluation: None,
+            nested_anova: None,
+            expanded_anova: None,
+            variance_components: None,
+            type1_study: None,
+            linearity_study: None,
+            attribute_agreement: None,
+            stability_study: None,
+            study_error: None,
             msg: Vec::new(),
         }
     }
@@ -71,6 +256,346 @@ impl GageStudyApp {
         }
         Default::default()
     }
+
+    fn clear_results(&mut self) {
+        self.gage_dataset = None;
+        self.plot_data.clear();
+        self.residuals = None;
+        self.outliers = None;
+        self.selected_rows.clear();
+        self.anova = None;
+        self.study_evaluation = None;
+        self.nested_anova = None;
+        self.expanded_anova = None;
+        self.variance_components = None;
+        self.type1_study = None;
+        self.linearity_study = None;
+        self.attribute_agreement = None;
+        self.stability_study = None;
+        self.study_error = None;
+    }
+
+    /// Keep one factor spec per factor column of the expanded dataset, after part and operator
+    fn sync_expanded_factors(&mut self) {
+        let mut names = vec!["Part".to_owned(), "Operator".to_owned()];
+        names.extend(ExpandedData::factor_names(&self.expanded_dataset));
+        self.expanded_factors.retain(|f| names.contains(&f.name));
+        for name in names.iter() {
+            if !self.expanded_factors.iter().any(|f| &f.name == name) {
+                self.expanded_factors.push(FactorSpec::new(name));
+            }
+        }
+        for f in self.expanded_factors.iter_mut() {
+            if f.nested_in.as_ref().is_some_and(|p| !names.contains(p)) {
+                f.nested_in = None;
+            }
+        }
+    }
+
+    fn has_data(&self) -> bool {
+        match self.study_type {
+            StudyType::Attribute => !self.attribute_dataset.is_empty(),
+            StudyType::Expanded => !self.expanded_dataset.is_empty(),
+            StudyType::Stability => !self.stability_dataset.is_empty(),
+            _ => !self.dataset.is_empty(),
+        }
+    }
+
+    /// Readings behind the study plots, which use the gage dataset built from them
+    fn set_plot_data(&mut self, plot_data: Vec<Data>) {
+        self.gage_dataset = Some(DataSet::from_data("ui_data", &plot_data));
+        self.plot_data = plot_data;
+    }
+
+    fn apply_data_entry_events(&mut self) {
+        let events = self.data_entry.take_events();
+        if !events.is_empty() {
+            self.clear_results();
+        }
+        for event in events {
+            match event {
+                DataEntryEvent::Reading { row, data } => {
+                    if row != self.dataset.len() {
+                        tracing::warn!(
+                            "DataEntryEvent::Reading: row {row} appended as {}",
+                            self.dataset.len()
+                        );
+                    }
+                    self.dataset.push(data);
+                }
+                DataEntryEvent::Undo(row) => {
+                    if row >= self.dataset.len() {
+                        continue;
+                    }
+                    self.dataset.remove(row);
+                    // Later rows move up one
+                    self.excluded_rows = std::mem::take(&mut self.excluded_rows)
+                        .into_iter()
+                        .filter(|excluded| *excluded != row)
+                        .map(|excluded| {
+                            if excluded > row {
+                                excluded - 1
+                            } else {
+                                excluded
+                            }
+                        })
+                        .collect();
+                }
+            }
+        }
+    }
+
+    /// Readings shown in the data table: those behind the study plots once
+    /// calculated, so that selected and suspect rows index them, otherwise as
+    /// loaded. Results are cleared whenever the loaded readings change, so the
+    /// plot data is never older than them.
+    fn table_data(&self) -> &Vec<Data> {
+        if self.plot_data.is_empty() {
+            &self.dataset
+        } else {
+            &self.plot_data
+        }
+    }
+
+    /// Nominal and limits from the readings, or as entered, with limits of
+    /// nominal ± tolerance / 2 when none were entered
+    fn spec_limits(&self) -> SpecLimits {
+        let spec_limits = SpecLimits::from_data(&self.plot_data);
+        let spec_limits = if self.use_spec_limits {
+            spec_limits.with_limits(self.lsl, self.usl)
+        } else {
+            spec_limits
+        };
+        spec_limits
+            .with_tolerance(self.tolerance)
+            .with_units(&self.units)
+    }
+
+    fn historical_stddev(&self) -> Option<f64> {
+        self.use_historical_stddev.then_some(self.historical_stddev)
+    }
+
+    /// Entered gage resolution, inferred from the readings when not given
+    fn resolution(&self) -> Option<f64> {
+        self.use_resolution.then_some(self.resolution)
+    }
+
+    /// Adds the gage resolution check to variance components calculated from
+    /// variable data
+    fn check_resolution(&mut self) {
+        let Some(components) = self.variance_components.take() else {
+            return;
+        };
+        // The readings the components were estimated from, without excluded rows
+        let check = ResolutionCheck::from_data(&self.plot_data, self.resolution());
+        self.variance_components = Some(match check {
+            Ok(check) => components.with_resolution(check),
+            Err(e) => {
+                tracing::warn!("ResolutionCheck::from_data: {e:?}");
+                components
+            }
+        });
+    }
+
+    /// Residuals of the part × operator model behind the study plots, and the
+    /// readings they and the replicate ranges flag as suspect
+    fn calculate_residuals(&mut self) {
+        if self.plot_data.is_empty() {
+            return;
+        }
+        match Residuals::from_data(&self.plot_data) {
+            Ok(residuals) => self.residuals = Some(residuals),
+            Err(e) => tracing::warn!("Residuals::from_data: {e:?}"),
+        }
+        match OutlierScreen::from_data(&self.plot_data) {
+            Ok(screen) if !screen.outliers.is_empty() => self.outliers = Some(screen),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("OutlierScreen::from_data: {e:?}"),
+        }
+    }
+
+    /// Only the crossed study can be recalculated without some readings, as it
+    /// falls back to REML when the design is unbalanced; the nested and
+    /// expanded ANOVA need every reading
+    fn can_exclude_readings(&self) -> bool {
+        self.study_type == StudyType::Crossed
+    }
+
+    /// Readings of `dataset` that are not excluded, in the order loaded
+    fn included_data(&self) -> Vec<Data> {
+        without_rows(self.dataset.clone(), &self.excluded_rows)
+    }
+
+    /// Leaves the suspect readings out and recalculates the study. The outlier
+    /// rows index the plot data, which is the included readings in order, so they
+    /// are mapped back to rows of `dataset` first.
+    fn exclude_outliers(&mut self) {
+        let Some(screen) = self.outliers.take() else {
+            return;
+        };
+        let included = (0..self.dataset.len())
+            .filter(|i| !self.excluded_rows.contains(i))
+            .collect::<Vec<usize>>();
+        self.excluded_rows.extend(
+            screen
+                .rows()
+                .into_iter()
+                .filter_map(|row| included.get(row)),
+        );
+        self.calculate();
+    }
+
+    fn restore_excluded(&mut self) {
+        self.excluded_rows.clear();
+        self.calculate();
+    }
+
+    fn calculate(&mut self) {
+        self.clear_results();
+        if !self.has_data() {
+            return;
+        }
+        match self.study_type {
+            StudyType::Crossed if !Reml::is_balanced(&self.included_data()) => {
+                // The ANOVA estimates assume a balanced design, fall back to REML
+                let dataset = self.included_data();
+                match Reml::from_data(&dataset) {
+                    Ok(reml) => {
+                        if !reml.converged {
+                            tracing::warn!(
+                                "REML did not converge after {} iterations",
+                                reml.iterations
+                            );
+                        }
+                        self.set_plot_data(dataset);
+                        self.variance_components = Some(
+                            VarianceComponents::from_reml(&reml)
+                                .with_tolerance(self.tolerance)
+                                .with_process_variation(self.process_variation)
+                                .with_historical_stddev(self.historical_stddev()),
+                        );
+                    }
+                    Err(e) => {
+                        tracing::error!("Reml::from_data: {e:?}");
+                        self.study_error = Some(e.to_string());
+                    }
+                }
+            }
+            StudyType::Crossed => {
+                self.set_plot_data(self.included_data());
+                self.anova = self.gage_dataset.as_ref().map(Anova::from_data);
+                self.study_evaluation = self.anova.as_ref().map(|a| {
+                    StudyEvaluation::from_anova(a)
+                        .with_tolerance(self.tolerance)
+                        .with_process_variation(self.process_variation)
+                });
+                self.variance_components = self
+                    .study_evaluation
+                    .as_ref()
+                    .zip(self.anova.as_ref())
+                    .map(|(s, a)| {
+                        VarianceComponents::from_study_evaluation(s, a, &self.plot_data)
+                            .with_historical_stddev(self.historical_stddev())
+                    });
+            }
+            StudyType::Expanded => {
+                match ExpandedAnova::from_data(
+                    &self.expanded_dataset,
+                    &self.expanded_factors,
+                    self.expanded_interactions,
+                ) {
+                    Ok(anova) => {
+                        let plot_data = self
+                            .expanded_dataset
+                            .iter()
+                            .map(|d| d.data.clone())
+                            .collect::<Vec<Data>>();
+                        self.set_plot_data(plot_data);
+                        self.variance_components = Some(
+                            VarianceComponents::from_expanded_anova(&anova)
+                                .with_tolerance(self.tolerance)
+                                .with_process_variation(self.process_variation)
+                                .with_historical_stddev(self.historical_stddev()),
+                        );
+                        self.expanded_anova = Some(anova);
+                    }
+                    Err(e) => {
+                        tracing::error!("ExpandedAnova::from_data: {e:?}");
+                        self.study_error = Some(e.to_string());
+                    }
+                }
+            }
+            StudyType::Nested => match NestedAnova::from_data(&self.dataset) {
+                Ok(anova) => {
+                    let plot_data = NestedAnova::qualified_data(&self.dataset);
+                    self.set_plot_data(plot_data);
+                    self.variance_components = Some(
+                        VarianceComponents::from_nested_anova(&anova)
+                            .with_tolerance(self.tolerance)
+                            .with_process_variation(self.process_variation)
+                            .with_historical_stddev(self.historical_stddev()),
+                    );
+                    self.nested_anova = Some(anova);
+                }
+                Err(e) => {
+                    tracing::error!("NestedAnova::from_data: {e:?}");
+                    self.study_error = Some(e.to_string());
+                }
+            },
+            StudyType::Type1 => match Type1Study::from_data(&self.dataset) {
+                Ok(study) => {
+                    self.type1_study = Some(
+                        study
+                            .with_tolerance(self.tolerance)
+                            .with_k_percent(self.type1_k_percent)
+                            .with_spread(self.type1_spread),
+                    );
+                }
+                Err(e) => {
+                    tracing::error!("Type1Study::from_data: {e:?}");
+                    self.study_error = Some(e.to_string());
+                }
+            },
+            StudyType::Linearity => match LinearityStudy::from_data(&self.dataset) {
+                Ok(study) => {
+                    self.linearity_study =
+                        Some(study.with_process_variation(self.linearity_process_variation));
+                }
+                Err(e) => {
+                    tracing::error!("LinearityStudy::from_data: {e:?}");
+                    self.study_error = Some(e.to_string());
+                }
+            },
+            StudyType::Attribute => match AttributeAgreement::from_data(&self.attribute_dataset) {
+                Ok(study) => {
+                    self.attribute_agreement =
+                        Some(study.with_pass_rating(&self.attribute_pass_rating));
+                }
+                Err(e) => {
+                    tracing::error!("AttributeAgreement::from_data: {e:?}");
+                    self.study_error = Some(e.to_string());
+                }
+            },
+            StudyType::Stability => {
+                let study = StabilityStudy::from_data(&self.stability_dataset)
+                    .and_then(|s| {
+                        s.with_chart(self.stability_chart_type, self.stability_subgroup_size)
+                    })
+                    .map(|s| s.with_rule_set(self.stability_rule_set));
+                match study {
+                    Ok(study) => {
+                        self.stability_study = Some(study);
+                    }
+                    Err(e) => {
+                        tracing::error!("StabilityStudy::from_data: {e:?}");
+                        self.study_error = Some(e.to_string());
+                    }
+                }
+            }
+        }
+        self.check_resolution();
+        self.calculate_residuals();
+    }
 }
 
 impl eframe::App for GageStudyApp {
@@ -88,8 +613,41 @@ impl eframe::App for GageStudyApp {
                         self.open_files.push(f.name);
                     } else {
                         self.dataset = f.content;
+                        self.excluded_rows.clear();
+                        self.data_entry.forget_rows();
+                        self.open_files = vec![f.name];
+                    };
+                    self.clear_results();
+                }
+                Message::AttributeFileOpen(f) => {
+                    if self.concatenate_data {
+                        self.attribute_dataset.extend(f.content);
+                        self.open_files.push(f.name);
+                    } else {
+                        self.attribute_dataset = f.content;
+                        self.open_files = vec![f.name];
+                    };
+                }
+                Message::ExpandedFileOpen(f) => {
+                    if self.concatenate_data {
+                        self.expanded_dataset.extend(f.content);
+                        self.open_files.push(f.name);
+                    } else {
+                        self.expanded_dataset = f.content;
+                        self.open_files = vec![f.name];
+                    };
+                    self.sync_expanded_factors();
+                    self.clear_results();
+                }
+                Message::StabilityFileOpen(f) => {
+                    if self.concatenate_data {
+                        self.stability_dataset.extend(f.content);
+                        self.open_files.push(f.name);
+                    } else {
+                        self.stability_dataset = f.content;
                         self.open_files = vec![f.name];
                     };
+                    self.clear_results();
                 }
                 Message::LogFile(bytes) => {
                     self.msg = bytes;
@@ -114,6 +672,23 @@ impl eframe::App for GageStudyApp {
             ui.checkbox(&mut self.concatenate_data, "Concatenate Files");
             let open_button = ui.add(egui::Button::new("Open..."));
             let demo_button = ui.add(egui::Button::new("Load Demo Data..."));
+            if ui.button("Plan Study...").clicked() {
+                self.show_planner = true;
+            }
+            if ui.button("Simulate Study...").clicked() {
+                self.show_simulator = true;
+            }
+            if ui.button("New Study from Plan...").clicked() {
+                self.show_worksheet_generator = true;
+            }
+            if ui.button("Guided Data Entry...").clicked() {
+                self.show_data_entry = true;
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button("Gauge Input...").clicked() {
+                self.show_gauge_input = true;
+                self.show_data_entry = true;
+            }
             ui.separator();
             ui.heading("Open Data Files: ");
             for f in self.open_files.iter() {
@@ -128,6 +703,22 @@ impl eframe::App for GageStudyApp {
                         .range(0..=99),
                 );
             });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.use_spec_limits, "LSL / USL: ")
+                    .on_hover_text("Nominal ± tolerance / 2 when not given");
+                ui.add_enabled(
+                    self.use_spec_limits,
+                    egui::DragValue::new(&mut self.lsl).speed(0.01),
+                );
+                ui.add_enabled(
+                    self.use_spec_limits,
+                    egui::DragValue::new(&mut self.usl).speed(0.01),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Units: ");
+                ui.add(egui::TextEdit::singleline(&mut self.units).desired_width(60.0));
+            });
             ui.horizontal(|ui| {
                 ui.label("Process Variation: ");
                 ui.add(
@@ -136,33 +727,196 @@ impl eframe::App for GageStudyApp {
                         .range(0..=99),
                 );
             });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.use_historical_stddev, "Historical σ: ");
+                ui.add_enabled(
+                    self.use_historical_stddev,
+                    egui::DragValue::new(&mut self.historical_stddev)
+                        .speed(0.01)
+                        .range(0.000001..=f64::MAX),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.use_resolution, "Resolution: ")
+                    .on_hover_text("Inferred from the readings when not given");
+                ui.add_enabled(
+                    self.use_resolution,
+                    egui::DragValue::new(&mut self.resolution)
+                        .speed(0.0001)
+                        .range(0.000001..=f64::MAX),
+                );
+            });
+            ui.separator();
+            ui.heading("Study Type");
+            egui::ComboBox::from_id_salt("study_type")
+                .selected_text(self.study_type.label())
+                .show_ui(ui, |ui| {
+                    for study_type in StudyType::ALL {
+                        ui.selectable_value(&mut self.study_type, study_type, study_type.label());
+                    }
+                });
+            if self.study_type == StudyType::Type1 {
+                ui.horizontal(|ui| {
+                    ui.label("K (% Tol): ");
+                    ui.add(
+                        egui::DragValue::new(&mut self.type1_k_percent)
+                            .speed(1.0)
+                            .range(1..=100),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Spread: ");
+                    ui.radio_value(&mut self.type1_spread, 6.0, "6σ");
+                    ui.radio_value(&mut self.type1_spread, 4.0, "4σ");
+                });
+            }
+            if self.study_type == StudyType::Linearity {
+                ui.horizontal(|ui| {
+                    ui.label("Process Variation (6σ): ");
+                    ui.add(
+                        egui::DragValue::new(&mut self.linearity_process_variation)
+                            .speed(0.1)
+                            .range(0.0001..=f64::MAX),
+                    );
+                });
+            }
+            if self.study_type == StudyType::Attribute {
+                ui.horizontal(|ui| {
+                    ui.label("Pass Rating: ");
+                    ui.text_edit_singleline(&mut self.attribute_pass_rating);
+                });
+            }
+            if self.study_type == StudyType::Stability {
+                ui.horizontal(|ui| {
+                    ui.label("Chart: ");
+                    for chart_type in ChartType::ALL {
+                        ui.radio_value(
+                            &mut self.stability_chart_type,
+                            chart_type,
+                            chart_type.label(),
+                        );
+                    }
+                });
+                if self.stability_chart_type == ChartType::XbarS {
+                    ui.horizontal(|ui| {
+                        ui.label("Subgroup size: ");
+                        ui.add(
+                            egui::DragValue::new(&mut self.stability_subgroup_size)
+                                .speed(0.1)
+                                .range(2..=50),
+                        );
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Run rules: ");
+                    egui::ComboBox::from_id_salt("stability_rule_set")
+                        .selected_text(self.stability_rule_set.label())
+                        .show_ui(ui, |ui| {
+                            for rule_set in RuleSet::ALL {
+                                ui.selectable_value(
+                                    &mut self.stability_rule_set,
+                                    rule_set,
+                                    rule_set.label(),
+                                );
+                            }
+                        });
+                });
+            }
+            if self.study_type == StudyType::Expanded {
+                ui.checkbox(&mut self.expanded_interactions, "2-way interactions");
+                let names = self
+                    .expanded_factors
+                    .iter()
+                    .map(|f| f.name.clone())
+                    .collect::<Vec<String>>();
+                egui::Grid::new("expanded_factors").show(ui, |ui| {
+                    ui.strong("Factor");
+                    ui.strong("Random");
+                    ui.strong("Nested in");
+                    ui.end_row();
+                    for f in self.expanded_factors.iter_mut() {
+                        ui.label(f.name.as_str());
+                        // Parts are always a random, crossed factor
+                        let editable = f.name != "Part";
+                        ui.add_enabled(editable, egui::Checkbox::without_text(&mut f.random));
+                        ui.add_enabled_ui(editable, |ui| {
+                            egui::ComboBox::from_id_salt(("nested_in", f.name.as_str()))
+                                .selected_text(f.nested_in.as_deref().unwrap_or("(crossed)"))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut f.nested_in, None, "(crossed)");
+                                    for name in names.iter().filter(|n| **n != f.name) {
+                                        ui.selectable_value(
+                                            &mut f.nested_in,
+                                            Some(name.clone()),
+                                            name.as_str(),
+                                        );
+                                    }
+                                });
+                        });
+                        ui.end_row();
+                    }
+                });
+            }
             ui.vertical(|ui| {
                 if ui
-                    .add_enabled(!self.dataset.is_empty(), egui::Button::new("Calculate..."))
+                    .add_enabled(self.has_data(), egui::Button::new("Calculate..."))
                     .clicked()
                 {
-                    self.gage_dataset = match self.dataset.len() {
-                        len if len > 0 => Some(DataSet::from_data("ui_data", &self.dataset)),
-                        _ => None,
-                    };
-                    self.anova = self.gage_dataset.as_ref().map(Anova::from_data);
-                    self.study_evaluation = match &self.anova {
-                        Some(a) => Some(
-                            StudyEvaluation::from_anova(a)
-                                .with_tolerance(self.tolerance)
-                                .with_process_variation(self.process_variation),
-                        ),
-                        None => None,
-                    };
+                    self.calculate();
                 }
                 if ui.button("Clear data...").clicked() {
                     self.dataset.clear();
-                    self.gage_dataset = None;
+                    self.excluded_rows.clear();
+                    self.data_entry.forget_rows();
+                    self.attribute_dataset.clear();
+                    self.expanded_dataset.clear();
+                    self.stability_dataset.clear();
                     self.open_files.clear();
-                    self.anova = None;
-                    self.study_evaluation = None;
+                    self.clear_results();
                 }
             });
+            if let Some(err) = &self.study_error {
+                ui.colored_label(Color32::RED, err);
+            }
+            if let Some(screen) = &self.outliers {
+                let rows = screen
+                    .outliers
+                    .iter()
+                    .map(|o| o.row.to_string())
+                    .collect::<Vec<String>>();
+                ui.colored_label(
+                    Color32::ORANGE,
+                    format!("⚠ Suspect readings in rows {}", rows.join(", ")),
+                )
+                .on_hover_text(
+                    screen
+                        .outliers
+                        .iter()
+                        .map(|o| format!("Row {}: {}", o.row, o.describe().replace('\n', "; ")))
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                );
+                if ui
+                    .add_enabled(
+                        self.can_exclude_readings(),
+                        egui::Button::new("Exclude and recalculate"),
+                    )
+                    .on_disabled_hover_text(
+                        "Only crossed studies can be recalculated without some readings",
+                    )
+                    .clicked()
+                {
+                    self.exclude_outliers();
+                }
+            }
+            if self.can_exclude_readings() && !self.excluded_rows.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} readings excluded", self.excluded_rows.len()));
+                    if ui.button("Restore").clicked() {
+                        self.restore_excluded();
+                    }
+                });
+            }
             // Event handling
             if open_button.clicked() {
                 let task = rfd::AsyncFileDialog::new()
@@ -171,6 +925,7 @@ impl eframe::App for GageStudyApp {
                     .set_directory("/")
                     .pick_file();
                 let message_sender = self.message_channel.0.clone();
+                let study_type = self.study_type;
 
                 execute(async move {
                     let file = task.await;
@@ -181,6 +936,54 @@ impl eframe::App for GageStudyApp {
                         let file_name = file.file_name();
                         let file_ext = Path::new(&file_name).extension().unwrap().to_str().unwrap();
                         //message_sender.send(Message::LogFile(file_content)).ok();
+                        if study_type == StudyType::Attribute {
+                            match AttributeData::from_raw(&file_content, file_ext) {
+                                Ok(data) => {
+                                    let _ = message_sender
+                                        .send(Message::AttributeFileOpen(AttributeFileInfo {
+                                            name: file.file_name(),
+                                            content: data,
+                                        }))
+                                        .map_err(|e| tracing::error!("Sender::send: {e:?}"));
+                                }
+                                Err(e) => {
+                                    tracing::error!("AttributeData::from_raw: {e:?}");
+                                }
+                            };
+                            return;
+                        }
+                        if study_type == StudyType::Expanded {
+                            match ExpandedData::from_raw(&file_content, file_ext) {
+                                Ok(data) => {
+                                    let _ = message_sender
+                                        .send(Message::ExpandedFileOpen(ExpandedFileInfo {
+                                            name: file.file_name(),
+                                            content: data,
+                                        }))
+                                        .map_err(|e| tracing::error!("Sender::send: {e:?}"));
+                                }
+                                Err(e) => {
+                                    tracing::error!("ExpandedData::from_raw: {e:?}");
+                                }
+                            };
+                            return;
+                        }
+                        if study_type == StudyType::Stability {
+                            match StabilityData::from_raw(&file_content, file_ext) {
+                                Ok(data) => {
+                                    let _ = message_sender
+                                        .send(Message::StabilityFileOpen(StabilityFileInfo {
+                                            name: file.file_name(),
+                                            content: data,
+                                        }))
+                                        .map_err(|e| tracing::error!("Sender::send: {e:?}"));
+                                }
+                                Err(e) => {
+                                    tracing::error!("StabilityData::from_raw: {e:?}");
+                                }
+                            };
+                            return;
+                        }
                         match Data::from_raw(&file_content, file_ext) {
                             Ok(data) => {
                                 let _ = message_sender
@@ -277,38 +1080,202 @@ impl eframe::App for GageStudyApp {
                     .color(Color32::GREEN)
                     .background_color(Color32::TRANSPARENT),
             );
+            ui.label("");
+            ui.label("Attribute agreement CSV data format:");
+            ui.label(
+                RichText::new(EXAMPLE_ATTRIBUTE_CSV)
+                    .monospace()
+                    .color(Color32::GREEN)
+                    .background_color(Color32::TRANSPARENT),
+            );
+            ui.label("");
+            ui.label("Stability study CSV data format:");
+            ui.label(
+                RichText::new(EXAMPLE_STABILITY_CSV)
+                    .monospace()
+                    .color(Color32::GREEN)
+                    .background_color(Color32::TRANSPARENT),
+            );
             ui.label(String::from_utf8(self.msg.clone()).unwrap().as_str());
         });
 
-        DataTableView::default().show(ctx, &self.dataset, &mut (!self.dataset.is_empty()));
+        DataTableView::default()
+            .with_outliers(&self.outliers)
+            .with_selection(
+                &self.selected_rows,
+                std::mem::take(&mut self.scroll_to_selection),
+            )
+            .show(ctx, self.table_data(), &mut (!self.table_data().is_empty()));
+        AttributeDataTableView::default().show(
+            ctx,
+            &self.attribute_dataset,
+            &mut (!self.attribute_dataset.is_empty()),
+        );
         AnovaTableView::default().show(ctx, &self.anova, &mut self.anova.is_some());
+        ExpandedAnovaTableView::default().show(
+            ctx,
+            &self.expanded_anova,
+            &mut self.expanded_anova.is_some(),
+        );
+        NestedAnovaTableView::default().show(
+            ctx,
+            &self.nested_anova,
+            &mut self.nested_anova.is_some(),
+        );
         VarCompTableView::default().show(
             ctx,
-            &self.study_evaluation,
-            &mut self.study_evaluation.is_some(),
+            &self.variance_components,
+            &mut self.variance_components.is_some(),
         );
         GageEvalTableView::default().show(
             ctx,
-            &self.study_evaluation,
-            &mut self.study_evaluation.is_some(),
+            &self.variance_components,
+            &mut self.variance_components.is_some(),
         );
-        StudyPlots::default().show(
-            ctx,
-            &self.gage_dataset,
+        // The data table shows the plot data, so the rows the plots select index it
+        let spec_limits = self.spec_limits();
+        for plot_type in [
             PlotType::PartMeasurement,
-            &mut self.gage_dataset.is_some(),
+            PlotType::OperatorMeasurement,
+            PlotType::XbarByOperator,
+            PlotType::PartOperatorInteraction,
+            PlotType::ComponentsOfVariation,
+            PlotType::RangeByOperator,
+        ] {
+            let mut open = match plot_type {
+                PlotType::ComponentsOfVariation => self.variance_components.is_some(),
+                _ => self.gage_dataset.is_some(),
+            };
+            let output = StudyPlots::default()
+                .with_data(&self.plot_data)
+                .with_outliers(&self.outliers)
+                .with_components(&self.variance_components)
+                .with_selection(&self.selected_rows)
+                .with_spec_limits(&spec_limits)
+                .show(ctx, &self.gage_dataset, plot_type, &mut open);
+            if let Some(rows) = output.selected {
+                self.selected_rows = rows;
+                self.scroll_to_selection = true;
+            }
+            if let Some((file_name, content)) = output.export {
+                save_file(file_name, content);
+            }
+        }
+        let output = ResidualPlots::default()
+            .with_data(&self.plot_data)
+            .with_outliers(&self.outliers)
+            .with_selection(&self.selected_rows)
+            .show(ctx, &self.residuals, &mut self.residuals.is_some());
+        if let Some(rows) = output.selected {
+            self.selected_rows = rows;
+            self.scroll_to_selection = true;
+        }
+        if let Some((file_name, content)) = output.export {
+            save_file(file_name, content);
+        }
+        Type1TableView::default().show(ctx, &self.type1_study, &mut self.type1_study.is_some());
+        if let Some((file_name, content)) =
+            Type1RunChart::default().show(ctx, &self.type1_study, &mut self.type1_study.is_some())
+        {
+            save_file(file_name, content);
+        }
+        LinearityTableView::default().show(
+            ctx,
+            &self.linearity_study,
+            &mut self.linearity_study.is_some(),
         );
-        StudyPlots::default().show(
+        if let Some((file_name, content)) = LinearityPlot::default().show(
             ctx,
-            &self.gage_dataset,
-            PlotType::OperatorMeasurement,
-            &mut self.gage_dataset.is_some(),
+            &self.linearity_study,
+            &mut self.linearity_study.is_some(),
+        ) {
+            save_file(file_name, content);
+        }
+        AttributeAgreementTableView::default().show(
+            ctx,
+            &self.attribute_agreement,
+            &mut self.attribute_agreement.is_some(),
         );
+        self.simulator.show(ctx, &mut self.show_simulator);
+        if let Some((file_name, content)) = self.planner.show(ctx, &mut self.show_planner) {
+            save_file(file_name, content.into_bytes());
+        }
+        if let Some((file_name, content)) = self.worksheet_generator.show(
+            ctx,
+            self.planner.selected_design(),
+            &mut self.show_worksheet_generator,
+        ) {
+            save_file(file_name, content.into_bytes());
+        }
+        self.data_entry.show(
+            ctx,
+            self.worksheet_generator.worksheet(),
+            &self.dataset,
+            &mut self.show_data_entry,
+        );
+        self.apply_data_entry_events();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.gauge_input.show(ctx, &mut self.show_gauge_input);
+            self.gauge_queue.extend(self.gauge_input.poll());
+            if !self.data_entry.is_active() && !self.gauge_queue.is_empty() {
+                self.gauge_input.report_error(format!(
+                    "{} readings ignored, start guided data entry to record them",
+                    self.gauge_queue.len()
+                ));
+                self.gauge_queue.clear();
+            }
+            // One reading at a time, so each is checked against the ones before it,
+            // holding the rest back while a suspected typo awaits confirmation
+            while self.data_entry.is_active() && !self.data_entry.is_awaiting_confirmation() {
+                let Some(value) = self.gauge_queue.pop_front() else {
+                    break;
+                };
+                self.data_entry.submit(value, &self.dataset);
+                self.apply_data_entry_events();
+            }
+        }
+        StabilityTableView::default().show(
+            ctx,
+            &self.stability_study,
+            &mut self.stability_study.is_some(),
+        );
+        if let Some((file_name, content)) = StabilityChart::default().show(
+            ctx,
+            &self.stability_study,
+            &mut self.stability_study.is_some(),
+        ) {
+            save_file(file_name, content);
+        }
     }
 }
 
 use std::future::Future;
 
+/// `items` without the entries at the indices in `rows`
+fn without_rows<T>(items: Vec<T>, rows: &BTreeSet<usize>) -> Vec<T> {
+    items
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| !rows.contains(i))
+        .map(|(_, item)| item)
+        .collect()
+}
+
+/// Prompts for a location and writes `content` to it; on the web this is a download
+fn save_file(file_name: String, content: Vec<u8>) {
+    let task = rfd::AsyncFileDialog::new()
+        .set_file_name(file_name)
+        .save_file();
+    execute(async move {
+        if let Some(file) = task.await {
+            if let Err(e) = file.write(&content).await {
+                tracing::error!("FileHandle::write: {e:?}");
+            }
+        }
+    });
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn execute<F: Future<Output = ()> + Send + 'static>(f: F) {
     // TODO: make custom executor
@@ -0,0 +1,355 @@
+use crate::AttributeData;
+use anyhow::{Result, bail};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Number of parts on which the ratings agreed, with the associated kappa
+pub struct AgreementRow {
+    pub source: String,
+    pub inspected: usize,
+    pub matched: usize,
+    pub kappa: f64,
+}
+
+impl AgreementRow {
+    /// Percent matched, none when no parts were inspected
+    pub fn percent(&self) -> Option<f64> {
+        ratio(self.matched, self.inspected).map(|r| r * 100.0)
+    }
+}
+
+/// Miss and false alarm rates of one appraiser against the known standard
+pub struct AppraiserRates {
+    pub operator: String,
+    pub misses: usize,
+    pub nonconforming: usize,
+    pub false_alarms: usize,
+    pub conforming: usize,
+}
+
+impl AppraiserRates {
+    /// Fraction of nonconforming ratings that were accepted, none without
+    /// nonconforming parts
+    pub fn miss_rate(&self) -> Option<f64> {
+        ratio(self.misses, self.nonconforming)
+    }
+
+    /// Fraction of conforming ratings that were rejected, none without
+    /// conforming parts
+    pub fn false_alarm_rate(&self) -> Option<f64> {
+        ratio(self.false_alarms, self.conforming)
+    }
+}
+
+/// Attribute agreement analysis for go/no-go and other categorical gages
+pub struct AttributeAgreement {
+    pub within_appraiser: Vec<AgreementRow>,
+    pub appraiser_vs_standard: Vec<AgreementRow>,
+    pub between_appraisers: AgreementRow,
+    pub all_vs_standard: Option<AgreementRow>,
+    pub pass_rating: String,
+    pub rates: Vec<AppraiserRates>,
+    /// Parts with no known standard, left out of the statistics against it
+    pub parts_without_standard: Vec<String>,
+    data: Vec<AttributeData>,
+}
+
+impl AttributeAgreement {
+    pub fn from_data(data: &[AttributeData]) -> Result<Self> {
+        if data.is_empty() {
+            bail!("Attribute study requires at least one rating");
+        }
+        // Known standard per part, if supplied
+        let mut standards: BTreeMap<&str, Option<&str>> = BTreeMap::new();
+        for d in data.iter() {
+            let entry = standards.entry(d.part.as_str()).or_insert(None);
+            match (*entry, d.standard.as_deref()) {
+                (Some(a), Some(b)) if a != b => {
+                    bail!("Part {} has more than one standard rating", d.part)
+                }
+                (None, Some(b)) => *entry = Some(b),
+                _ => {}
+            }
+        }
+        let parts_without_standard = standards
+            .iter()
+            .filter(|(_, standard)| standard.is_none())
+            .map(|(part, _)| part.to_string())
+            .collect::<Vec<String>>();
+        let has_standard = parts_without_standard.len() < standards.len();
+
+        let mut by_operator: BTreeMap<&str, BTreeMap<&str, Vec<&str>>> = BTreeMap::new();
+        let mut by_part: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for d in data.iter() {
+            by_operator
+                .entry(d.operator.as_str())
+                .or_default()
+                .entry(d.part.as_str())
+                .or_default()
+                .push(d.rating.as_str());
+            by_part
+                .entry(d.part.as_str())
+                .or_default()
+                .push(d.rating.as_str());
+        }
+
+        let mut within_appraiser = Vec::new();
+        let mut appraiser_vs_standard = Vec::new();
+        for (operator, parts) in by_operator.iter() {
+            let subjects = parts.values().cloned().collect::<Vec<Vec<&str>>>();
+            within_appraiser.push(AgreementRow {
+                source: operator.to_string(),
+                inspected: subjects.len(),
+                matched: subjects.iter().filter(|r| all_equal(r)).count(),
+                kappa: fleiss_kappa(&subjects),
+            });
+            if has_standard {
+                let rated = parts
+                    .iter()
+                    .filter_map(|(part, ratings)| Some((standards[*part]?, ratings)))
+                    .collect::<Vec<(&str, &Vec<&str>)>>();
+                appraiser_vs_standard.push(against_standard(operator, &rated));
+            }
+        }
+
+        let subjects = by_part.values().cloned().collect::<Vec<Vec<&str>>>();
+        let between_appraisers = AgreementRow {
+            source: "All appraisers".to_string(),
+            inspected: subjects.len(),
+            matched: subjects.iter().filter(|r| all_equal(r)).count(),
+            kappa: fleiss_kappa(&subjects),
+        };
+        let all_vs_standard = has_standard.then(|| {
+            let rated = by_part
+                .iter()
+                .filter_map(|(part, ratings)| Some((standards[*part]?, ratings)))
+                .collect::<Vec<(&str, &Vec<&str>)>>();
+            against_standard("All appraisers", &rated)
+        });
+
+        let mut study = Self {
+            within_appraiser,
+            appraiser_vs_standard,
+            between_appraisers,
+            all_vs_standard,
+            pass_rating: "pass".to_string(),
+            rates: Vec::new(),
+            parts_without_standard,
+            data: data.to_owned(),
+        };
+        study.evaluate();
+        Ok(study)
+    }
+
+    /// Set the rating that denotes an accepted (conforming) part; ratings are
+    /// normalised on load, so this is normalised the same way
+    pub fn with_pass_rating(mut self, pass_rating: &str) -> Self {
+        self.pass_rating = AttributeData::normalize(pass_rating);
+        self.evaluate();
+        self
+    }
+
+    fn evaluate(&mut self) {
+        let is_pass = |r: &str| r == self.pass_rating;
+        let mut rates: BTreeMap<&str, AppraiserRates> = BTreeMap::new();
+        for d in self.data.iter() {
+            let Some(standard) = d.standard.as_deref() else {
+                continue;
+            };
+            let entry = rates
+                .entry(d.operator.as_str())
+                .or_insert_with(|| AppraiserRates {
+                    operator: d.operator.clone(),
+                    misses: 0,
+                    nonconforming: 0,
+                    false_alarms: 0,
+                    conforming: 0,
+                });
+            if is_pass(standard) {
+                entry.conforming += 1;
+                if !is_pass(&d.rating) {
+                    entry.false_alarms += 1;
+                }
+            } else {
+                entry.nonconforming += 1;
+                if is_pass(&d.rating) {
+                    entry.misses += 1;
+                }
+            }
+        }
+        self.rates = rates.into_values().collect();
+    }
+}
+
+/// Agreement of the ratings of each part with its standard, for parts given as
+/// (standard, ratings)
+fn against_standard(source: &str, rated: &[(&str, &Vec<&str>)]) -> AgreementRow {
+    let pairs = rated
+        .iter()
+        .flat_map(|(standard, ratings)| ratings.iter().map(move |r| (*r, *standard)))
+        .collect::<Vec<(&str, &str)>>();
+    AgreementRow {
+        source: source.to_string(),
+        inspected: rated.len(),
+        matched: rated
+            .iter()
+            .filter(|(standard, ratings)| ratings.iter().all(|r| r == standard))
+            .count(),
+        kappa: cohen_kappa(&pairs),
+    }
+}
+
+fn ratio(count: usize, total: usize) -> Option<f64> {
+    (total > 0).then(|| count as f64 / total as f64)
+}
+
+fn all_equal(ratings: &[&str]) -> bool {
+    ratings.windows(2).all(|w| w[0] == w[1])
+}
+
+/// Fleiss' kappa for subjects rated by possibly differing numbers of raters
+fn fleiss_kappa(subjects: &[Vec<&str>]) -> f64 {
+    let categories = subjects
+        .iter()
+        .flatten()
+        .copied()
+        .collect::<BTreeSet<&str>>();
+    let subjects = subjects
+        .iter()
+        .filter(|s| s.len() > 1)
+        .collect::<Vec<&Vec<&str>>>();
+    if subjects.is_empty() {
+        return f64::NAN;
+    }
+    let total = subjects.iter().map(|s| s.len()).sum::<usize>() as f64;
+    if total == 0.0 {
+        return f64::NAN;
+    }
+    let mut p_bar = 0.0;
+    let mut p_e = 0.0;
+    for c in categories.iter() {
+        let count = subjects
+            .iter()
+            .map(|s| s.iter().filter(|r| *r == c).count())
+            .sum::<usize>() as f64;
+        p_e += (count / total).powi(2);
+    }
+    for s in subjects.iter() {
+        let n = s.len() as f64;
+        let agreement = categories
+            .iter()
+            .map(|c| s.iter().filter(|r| *r == c).count() as f64)
+            .map(|n_ij| n_ij * (n_ij - 1.0))
+            .sum::<f64>();
+        p_bar += agreement / (n * (n - 1.0));
+    }
+    p_bar /= subjects.len() as f64;
+    (p_bar - p_e) / (1.0 - p_e)
+}
+
+/// Cohen's kappa for paired (rating, standard) observations
+fn cohen_kappa(pairs: &[(&str, &str)]) -> f64 {
+    if pairs.is_empty() {
+        return f64::NAN;
+    }
+    let n = pairs.len() as f64;
+    let categories = pairs
+        .iter()
+        .flat_map(|(a, b)| [*a, *b])
+        .collect::<BTreeSet<&str>>();
+    let p_o = pairs.iter().filter(|(a, b)| a == b).count() as f64 / n;
+    let p_e = categories
+        .iter()
+        .map(|c| {
+            let rated = pairs.iter().filter(|(a, _)| a == c).count() as f64 / n;
+            let standard = pairs.iter().filter(|(_, b)| b == c).count() as f64 / n;
+            rated * standard
+        })
+        .sum::<f64>();
+    (p_o - p_e) / (1.0 - p_e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rating(part: &str, operator: &str, rating: &str, standard: Option<&str>) -> AttributeData {
+        AttributeData {
+            name: format!("{part}-{operator}"),
+            part: part.to_owned(),
+            operator: operator.to_owned(),
+            replicate: 1,
+            rating: rating.to_owned(),
+            standard: standard.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn cohen_kappa_matches_reference() {
+        // 50 paired ratings with 20 yes/yes, 5 yes/no, 10 no/yes and 15 no/no,
+        // the textbook example with kappa = 0.4
+        let mut pairs = Vec::new();
+        pairs.extend(std::iter::repeat_n(("yes", "yes"), 20));
+        pairs.extend(std::iter::repeat_n(("yes", "no"), 5));
+        pairs.extend(std::iter::repeat_n(("no", "yes"), 10));
+        pairs.extend(std::iter::repeat_n(("no", "no"), 15));
+        assert!((cohen_kappa(&pairs) - 0.4).abs() < 1e-12);
+    }
+
+    #[test]
+    fn fleiss_kappa_matches_reference() {
+        // Fleiss (1971) worked example: 10 subjects, 14 raters, 5 categories,
+        // kappa = 0.210
+        let counts = [
+            [0, 0, 0, 0, 14],
+            [0, 2, 6, 4, 2],
+            [0, 0, 3, 5, 6],
+            [0, 3, 9, 2, 0],
+            [2, 2, 8, 1, 1],
+            [7, 7, 0, 0, 0],
+            [3, 2, 6, 3, 0],
+            [2, 5, 3, 2, 2],
+            [6, 5, 2, 1, 0],
+            [0, 2, 2, 3, 7],
+        ];
+        let categories = ["1", "2", "3", "4", "5"];
+        let subjects = counts
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(categories.iter())
+                    .flat_map(|(n, c)| std::iter::repeat_n(*c, *n))
+                    .collect::<Vec<&str>>()
+            })
+            .collect::<Vec<Vec<&str>>>();
+        assert!((fleiss_kappa(&subjects) - 0.20993).abs() < 1e-4);
+    }
+
+    #[test]
+    fn parts_without_standard_are_left_out() {
+        let data = [
+            rating("1", "A", "pass", Some("pass")),
+            rating("1", "B", "pass", Some("pass")),
+            rating("2", "A", "fail", Some("fail")),
+            rating("2", "B", "pass", Some("fail")),
+            rating("3", "A", "pass", None),
+            rating("3", "B", "fail", None),
+        ];
+        let study = AttributeAgreement::from_data(&data).unwrap();
+        assert_eq!(study.parts_without_standard, vec!["3".to_owned()]);
+        let all = study.all_vs_standard.unwrap();
+        assert_eq!((all.inspected, all.matched), (2, 1));
+        assert_eq!(study.appraiser_vs_standard[0].percent(), Some(100.0));
+        assert_eq!(study.rates[1].miss_rate(), Some(1.0));
+    }
+
+    #[test]
+    fn empty_rates_have_no_percentage() {
+        let data = [
+            rating("1", "A", "pass", Some("pass")),
+            rating("1", "A", "pass", Some("pass")),
+        ];
+        let study = AttributeAgreement::from_data(&data).unwrap();
+        assert_eq!(study.rates[0].miss_rate(), None);
+        assert_eq!(study.rates[0].false_alarm_rate(), Some(0.0));
+    }
+}
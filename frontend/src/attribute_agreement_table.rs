@@ -0,0 +1,220 @@
+use crate::{AttributeAgreement, attribute_agreement::AgreementRow};
+use eframe::egui;
+/// Agreement within, between and against the standard for each appraiser, with miss and false alarm rates
+pub struct AttributeAgreementTableView<'a> {
+    pub striped: bool,
+    pub resizable: bool,
+    pub dataset: &'a Option<AttributeAgreement>,
+}
+
+impl Default for AttributeAgreementTableView<'_> {
+    fn default() -> Self {
+        Self {
+            striped: true,
+            resizable: true,
+            dataset: &None,
+        }
+    }
+}
+
+impl<'a> AttributeAgreementTableView<'a> {
+    pub fn name(&self) -> &'static str {
+        "☰ Attribute Agreement Table"
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        dataset: &'a Option<AttributeAgreement>,
+        open: &mut bool,
+    ) {
+        self.dataset = dataset;
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                self.ui(ui);
+            });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.style_mut().override_text_style = Some(egui::style::TextStyle::Monospace);
+        if let Some(study) = self.dataset {
+            if !study.parts_without_standard.is_empty() {
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!(
+                        "⚠ No standard for parts {}, left out of the vs-standard statistics",
+                        study.parts_without_standard.join(", ")
+                    ),
+                );
+            }
+        }
+        use egui_extras::{Size, StripBuilder};
+        StripBuilder::new(ui)
+            .size(Size::remainder().at_least(100.0)) // for the agreement table
+            .size(Size::exact(120.0)) // for the miss/false alarm table
+            .vertical(|mut strip| {
+                strip.cell(|ui| {
+                    egui::ScrollArea::horizontal()
+                        .id_salt("agreement_scroll")
+                        .show(ui, |ui| {
+                            self.agreement_table_ui(ui);
+                        });
+                });
+                strip.cell(|ui| {
+                    egui::ScrollArea::horizontal()
+                        .id_salt("rates_scroll")
+                        .show(ui, |ui| {
+                            self.rates_table_ui(ui);
+                        });
+                });
+            });
+    }
+
+    fn agreement_table_ui(&mut self, ui: &mut egui::Ui) {
+        use egui_extras::{Column, TableBuilder};
+
+        let table = TableBuilder::new(ui)
+            .id_salt("agreement_table")
+            .striped(self.striped)
+            .cell_layout(egui::Layout::right_to_left(egui::Align::Center))
+            .column(
+                Column::initial(180.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+            )
+            .columns(
+                Column::initial(90.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+                4,
+            )
+            .min_scrolled_height(0.0);
+
+        table
+            .header(20.0, |mut header| {
+                for title in ["Source", "# Inspected", "# Matched", "Percent", "Kappa"] {
+                    header.col(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.strong(title);
+                        });
+                    });
+                }
+            })
+            .body(|mut body| {
+                if let Some(study) = self.dataset {
+                    let row_height = 18.0;
+                    let mut sections: Vec<(&str, Vec<&AgreementRow>)> = vec![
+                        ("Within Appraisers", study.within_appraiser.iter().collect()),
+                        (
+                            "Each Appraiser vs Standard",
+                            study.appraiser_vs_standard.iter().collect(),
+                        ),
+                        ("Between Appraisers", vec![&study.between_appraisers]),
+                    ];
+                    if let Some(all) = &study.all_vs_standard {
+                        sections.push(("All Appraisers vs Standard", vec![all]));
+                    }
+                    for (title, rows) in sections.into_iter() {
+                        if rows.is_empty() {
+                            continue;
+                        }
+                        body.row(row_height, |mut row| {
+                            row.col(|ui| {
+                                ui.strong(title);
+                            });
+                            for _ in 0..4 {
+                                row.col(|ui| {
+                                    ui.label("");
+                                });
+                            }
+                        });
+                        for r in rows.into_iter() {
+                            body.row(row_height, |mut row| {
+                                row.col(|ui| {
+                                    ui.label(r.source.clone());
+                                });
+                                row.col(|ui| {
+                                    ui.label(format!("{:>4}", r.inspected));
+                                });
+                                row.col(|ui| {
+                                    ui.label(format!("{:>4}", r.matched));
+                                });
+                                row.col(|ui| {
+                                    ui.label(match r.percent() {
+                                        Some(percent) => format!("{percent:>6.2}"),
+                                        None => format!("{:>6}", "-"),
+                                    });
+                                });
+                                row.col(|ui| {
+                                    ui.label(if r.kappa.is_finite() {
+                                        format!("{:>7.4}", r.kappa)
+                                    } else {
+                                        format!("{:>7}", "-")
+                                    });
+                                });
+                            });
+                        }
+                    }
+                }
+            });
+    }
+
+    fn rates_table_ui(&mut self, ui: &mut egui::Ui) {
+        use egui_extras::{Column, TableBuilder};
+
+        let table = TableBuilder::new(ui)
+            .id_salt("rates_table")
+            .striped(self.striped)
+            .cell_layout(egui::Layout::right_to_left(egui::Align::Center))
+            .columns(
+                Column::initial(100.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+                3,
+            )
+            .min_scrolled_height(0.0);
+
+        table
+            .header(20.0, |mut header| {
+                for title in ["Appraiser", "Miss Rate", "False Alarm"] {
+                    header.col(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.strong(title);
+                        });
+                    });
+                }
+            })
+            .body(|mut body| {
+                if let Some(study) = self.dataset {
+                    let row_height = 18.0;
+                    for r in study.rates.iter() {
+                        body.row(row_height, |mut row| {
+                            row.col(|ui| {
+                                ui.label(r.operator.clone());
+                            });
+                            row.col(|ui| {
+                                ui.label(rate(r.miss_rate(), r.misses, r.nonconforming));
+                            });
+                            row.col(|ui| {
+                                ui.label(rate(r.false_alarm_rate(), r.false_alarms, r.conforming));
+                            });
+                        });
+                    }
+                }
+            });
+    }
+}
+
+/// Rate as a percentage with its counts, a dash when there was nothing to count
+fn rate(rate: Option<f64>, count: usize, total: usize) -> String {
+    match rate {
+        Some(rate) => format!("{:>6.2}% ({count}/{total})", rate * 100.0),
+        None => format!("{:>7} ({count}/{total})", "-"),
+    }
+}
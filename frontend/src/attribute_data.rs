@@ -0,0 +1,76 @@
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+/// A single categorical rating from an attribute (go/no-go) gage study
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AttributeData {
+    pub name: String,
+    pub part: String,
+    pub operator: String,
+    pub replicate: u32,
+    pub rating: String,
+    /// Known standard (reference) rating for the part, if available
+    #[serde(default)]
+    pub standard: Option<String>,
+}
+
+impl AttributeData {
+    pub fn from_raw(raw: &[u8], ext: &str) -> Result<Vec<Self>> {
+        match ext.to_lowercase().as_str() {
+            "json" => Self::from_raw_json(raw),
+            "csv" => Self::from_raw_csv(raw),
+            _ => bail!("Unsupported file extension: {ext}"),
+        }
+    }
+
+    pub fn from_raw_json(raw: &[u8]) -> Result<Vec<Self>> {
+        let data: Vec<Self> = serde_json::from_slice(raw)?;
+        Ok(data.into_iter().map(Self::normalized).collect())
+    }
+
+    pub fn from_raw_csv(raw: &[u8]) -> Result<Vec<Self>> {
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(raw);
+        let mut data = Vec::new();
+        for record in reader.deserialize() {
+            let record: Self = record?;
+            data.push(record.normalized());
+        }
+        Ok(data)
+    }
+
+    /// Ratings compared case-insensitively and without surrounding blanks, so
+    /// "Pass" and "pass " are one category
+    pub fn normalize(rating: &str) -> String {
+        rating.trim().to_lowercase()
+    }
+
+    /// Ratings and standard normalised, a blank standard being unknown
+    fn normalized(mut self) -> Self {
+        self.rating = Self::normalize(&self.rating);
+        self.standard = self
+            .standard
+            .map(|s| Self::normalize(&s))
+            .filter(|s| !s.is_empty());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratings_are_normalized_on_load() {
+        let csv = b"name,part,operator,replicate,rating,standard\n\
+                    a,1,A,1,Pass,PASS\n\
+                    b,1,B,1,pass ,\n";
+        let data = AttributeData::from_raw_csv(csv).unwrap();
+        assert_eq!(data[0].rating, "pass");
+        assert_eq!(data[0].standard.as_deref(), Some("pass"));
+        assert_eq!(data[1].rating, "pass");
+        assert_eq!(data[1].standard, None);
+    }
+}
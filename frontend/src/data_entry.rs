@@ -0,0 +1,403 @@
+use crate::{Worksheet, WorksheetRow, make_data};
+use anyhow::{Result, bail};
+use eframe::egui::{self, RichText};
+use gage_study::data::Data;
+
+/// Readings further than this many robust standard deviations from the other
+/// readings of the same part are flagged as likely typos
+const TYPO_LIMIT: f64 = 5.0;
+
+/// Changes to the dataset, to be applied in order before anything else
+/// changes it, as the rows are worked out from its length on submission
+pub enum DataEntryEvent {
+    /// A confirmed reading to append to the dataset, where it becomes `row`
+    Reading { row: usize, data: Data },
+    /// Remove the reading this form entered at `row`
+    Undo(usize),
+}
+
+/// Guided data entry: steps through a planned run order, one reading at a time
+#[derive(Default)]
+pub struct DataEntry {
+    sequence: Vec<WorksheetRow>,
+    position: usize,
+    /// For each run passed so far, the dataset row of the reading entered, none
+    /// when the run was skipped or the reading is no longer in the dataset
+    entered: Vec<Option<usize>>,
+    input: String,
+    /// Reading held back until the operator confirms a suspected typo
+    suspect: Option<(f64, String)>,
+    error: Option<String>,
+    events: Vec<DataEntryEvent>,
+}
+
+impl DataEntry {
+    pub fn name(&self) -> &'static str {
+        "☰ Guided Data Entry"
+    }
+
+    pub fn start(&mut self, sequence: Vec<WorksheetRow>) {
+        *self = Self {
+            sequence,
+            ..Default::default()
+        };
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.current().is_some()
+    }
+
+    /// The next planned reading, if any remain
+    pub fn current(&self) -> Option<&WorksheetRow> {
+        self.sequence.get(self.position)
+    }
+
+    /// Enter a reading for the current run. `dataset` holds the readings so far and
+    /// is used to detect likely typos, which must be confirmed by entering the
+    /// same value again.
+    pub fn submit(&mut self, value: f64, dataset: &[Data]) {
+        let Some(part) = self.current().map(|r| r.part.clone()) else {
+            return;
+        };
+        if let Some((suspect, _)) = self.suspect.take() {
+            if suspect == value {
+                self.accept(value, dataset.len());
+                return;
+            }
+        }
+        match typo_check(value, &part, dataset) {
+            Some(reason) => self.suspect = Some((value, reason)),
+            None => self.accept(value, dataset.len()),
+        }
+    }
+
+    /// Steps back one run, removing its reading unless the run was skipped
+    pub fn undo(&mut self) {
+        let Some(entered) = self.entered.pop() else {
+            return;
+        };
+        self.position -= 1;
+        self.input.clear();
+        self.suspect = None;
+        if let Some(row) = entered {
+            self.events.push(DataEntryEvent::Undo(row));
+        }
+    }
+
+    /// Stops undo from removing the readings entered so far, for when the dataset
+    /// they were added to is replaced or cleared
+    pub fn forget_rows(&mut self) {
+        self.entered.iter_mut().for_each(|row| *row = None);
+    }
+
+    /// Moves on to the next run without a reading
+    pub fn skip(&mut self) {
+        if !self.is_active() {
+            return;
+        }
+        self.entered.push(None);
+        self.position += 1;
+        self.input.clear();
+        self.suspect = None;
+    }
+
+    /// Whether a suspected typo is held back until it is entered again
+    pub fn is_awaiting_confirmation(&self) -> bool {
+        self.suspect.is_some()
+    }
+
+    /// Takes the events produced since the last call
+    pub fn take_events(&mut self) -> Vec<DataEntryEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// `worksheet` is the run order entry can be (re)started from
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        worksheet: Option<&Worksheet>,
+        dataset: &[Data],
+        open: &mut bool,
+    ) {
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                self.ui(ui, worksheet, dataset);
+            });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, worksheet: Option<&Worksheet>, dataset: &[Data]) {
+        match worksheet {
+            Some(worksheet) => {
+                if ui
+                    .button(format!(
+                        "Start from run order ({} runs)",
+                        worksheet.rows.len()
+                    ))
+                    .clicked()
+                {
+                    self.start(worksheet.rows.clone());
+                }
+            }
+            None => {
+                ui.label("Generate a run order in \"New Study from Plan\" to start entry.");
+            }
+        }
+        ui.separator();
+        let total = self.sequence.len();
+        let Some(row) = self.current().cloned() else {
+            if total > 0 {
+                ui.heading(format!("All {total} readings entered"));
+            }
+            return;
+        };
+        ui.label(format!("Run {} of {}", self.position + 1, total));
+        ui.label(
+            RichText::new(format!("Part: {}", row.part))
+                .size(32.0)
+                .strong(),
+        );
+        ui.label(
+            RichText::new(format!("Operator: {}", row.operator))
+                .size(32.0)
+                .strong(),
+        );
+        ui.label(
+            RichText::new(format!("Replicate: {}", row.replicate))
+                .size(32.0)
+                .strong(),
+        );
+        ui.separator();
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.input)
+                .font(egui::TextStyle::Heading)
+                .hint_text("reading, then Enter")
+                .desired_width(f32::INFINITY),
+        );
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            // An empty entry keeps a suspect reading that came from the gauge
+            let suspect = self.suspect.as_ref().map(|(value, _)| *value);
+            match suspect.filter(|_| self.input.trim().is_empty()) {
+                Some(value) => self.submit(value, dataset),
+                None => match parse_reading(&self.input) {
+                    Ok(value) => {
+                        self.error = None;
+                        self.submit(value, dataset);
+                    }
+                    Err(e) => self.error = Some(e.to_string()),
+                },
+            }
+        }
+        // Keep the cursor in the entry box unless another widget has focus
+        if ui.memory(|m| m.focused().is_none()) {
+            response.request_focus();
+        }
+        if let Some((value, reason)) = &self.suspect {
+            ui.colored_label(
+                egui::Color32::ORANGE,
+                format!("⚠ {value} looks like a typo: {reason}. Press Enter again to keep it."),
+            );
+        }
+        if let Some(err) = &self.error {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!self.entered.is_empty(), egui::Button::new("Undo last"))
+                .clicked()
+            {
+                self.undo();
+            }
+            if ui.button("Skip").clicked() {
+                self.skip();
+            }
+        });
+    }
+
+    /// Accepts `value` for the current run, with `len` readings in the dataset
+    /// before any events not yet applied
+    fn accept(&mut self, value: f64, len: usize) {
+        let Some(row) = self.current() else {
+            return;
+        };
+        let pending = self
+            .events
+            .iter()
+            .map(|event| match event {
+                DataEntryEvent::Reading { .. } => 1,
+                DataEntryEvent::Undo(_) => -1,
+            })
+            .sum::<isize>();
+        let index = len.saturating_add_signed(pending);
+        match make_data(
+            &row.name,
+            &row.part,
+            &row.operator,
+            row.replicate,
+            value,
+            row.nominal,
+        ) {
+            Ok(data) => self
+                .events
+                .push(DataEntryEvent::Reading { row: index, data }),
+            Err(e) => {
+                tracing::error!("DataEntry::accept: {e:?}");
+                self.error = Some(e.to_string());
+                return;
+            }
+        }
+        self.entered.push(Some(index));
+        self.position += 1;
+        self.input.clear();
+        self.suspect = None;
+    }
+}
+
+fn parse_reading(input: &str) -> Result<f64> {
+    // Accept a decimal comma as typed on some keyboards
+    let input = input.trim().replace(',', ".");
+    match input.parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        _ => bail!("\"{input}\" is not a number"),
+    }
+}
+
+/// Reason the reading looks like a typo, compared with the other readings of the part
+fn typo_check(value: f64, part: &str, dataset: &[Data]) -> Option<String> {
+    let mut others = dataset
+        .iter()
+        .filter(|d| d.part == part)
+        .map(|d| d.measured)
+        .collect::<Vec<f64>>();
+    if others.len() < 2 {
+        return None;
+    }
+    others.sort_by(f64::total_cmp);
+    let median = others[others.len() / 2];
+    let mut deviations = others
+        .iter()
+        .map(|v| (v - median).abs())
+        .collect::<Vec<f64>>();
+    deviations.sort_by(f64::total_cmp);
+    // Robust spread; fall back to a fraction of the value when all readings agree
+    let spread = (1.4826 * deviations[deviations.len() / 2]).max(median.abs() * 1e-3);
+    if spread <= 0.0 || (value - median).abs() <= TYPO_LIMIT * spread {
+        return None;
+    }
+    let slipped = [10.0, 0.1]
+        .iter()
+        .any(|f| (value * f - median).abs() <= TYPO_LIMIT * spread);
+    Some(if slipped {
+        format!("decimal point slip? other readings of {part} are near {median}")
+    } else {
+        format!("other readings of {part} are near {median}")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence(runs: usize) -> Vec<WorksheetRow> {
+        (1..=runs)
+            .map(|run| WorksheetRow {
+                run,
+                name: "Study".to_owned(),
+                part: format!("P{run}"),
+                operator: "A".to_owned(),
+                replicate: 1,
+                nominal: 0.0,
+            })
+            .collect()
+    }
+
+    fn undone_rows(entry: &mut DataEntry) -> Vec<usize> {
+        entry
+            .take_events()
+            .iter()
+            .filter_map(|e| match e {
+                DataEntryEvent::Undo(row) => Some(*row),
+                DataEntryEvent::Reading { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn undo_steps_back_over_skipped_runs() {
+        let mut entry = DataEntry::default();
+        entry.start(sequence(3));
+        entry.submit(1.0, &[]);
+        entry.skip();
+        entry.take_events();
+
+        entry.undo();
+        assert!(undone_rows(&mut entry).is_empty());
+        assert_eq!(entry.current().unwrap().run, 2);
+
+        entry.undo();
+        assert_eq!(undone_rows(&mut entry), vec![0]);
+        assert_eq!(entry.current().unwrap().run, 1);
+    }
+
+    #[test]
+    fn suspect_reading_waits_for_confirmation() {
+        let dataset = [10.0, 10.1, 10.2]
+            .iter()
+            .enumerate()
+            .map(|(i, measured)| make_data("Study", "P1", "B", i + 1, *measured, 0.0).unwrap())
+            .collect::<Vec<Data>>();
+        let mut entry = DataEntry::default();
+        entry.start(sequence(2));
+        entry.submit(101.0, &dataset);
+        assert!(entry.is_awaiting_confirmation());
+        assert!(entry.take_events().is_empty());
+
+        entry.submit(101.0, &dataset);
+        assert!(!entry.is_awaiting_confirmation());
+        assert_eq!(entry.take_events().len(), 1);
+        assert_eq!(entry.current().unwrap().run, 2);
+    }
+
+    #[test]
+    fn undo_never_removes_readings_entered_elsewhere() {
+        let mut entry = DataEntry::default();
+        entry.start(sequence(2));
+        entry.skip();
+        entry.undo();
+        entry.undo();
+        assert!(undone_rows(&mut entry).is_empty());
+        assert_eq!(entry.current().unwrap().run, 1);
+    }
+
+    #[test]
+    fn undo_removes_the_row_the_reading_was_entered_at() {
+        let loaded = [10.0, 10.1]
+            .iter()
+            .enumerate()
+            .map(|(i, measured)| make_data("Study", "P9", "B", i + 1, *measured, 0.0).unwrap())
+            .collect::<Vec<Data>>();
+        let mut entry = DataEntry::default();
+        entry.start(sequence(3));
+        entry.submit(1.0, &loaded);
+        entry.submit(2.0, &loaded);
+        let rows = entry
+            .take_events()
+            .iter()
+            .filter_map(|e| match e {
+                DataEntryEvent::Reading { row, .. } => Some(*row),
+                DataEntryEvent::Undo(_) => None,
+            })
+            .collect::<Vec<usize>>();
+        assert_eq!(rows, vec![2, 3]);
+
+        // Readings from a file appended since do not move the entered rows
+        entry.undo();
+        assert_eq!(undone_rows(&mut entry), vec![3]);
+
+        entry.forget_rows();
+        entry.undo();
+        assert!(undone_rows(&mut entry).is_empty());
+    }
+}
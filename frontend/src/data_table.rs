@@ -1,3 +1,4 @@
+use crate::OutlierScreen;
 use eframe::egui;
 use gage_study::data::Data;
 /// Shows off a table with dynamic layout
@@ -5,6 +6,12 @@ pub struct DataTableView {
     pub striped: bool,
     pub resizable: bool,
     pub dataset: Vec<Data>,
+    /// Rows highlighted and scrolled into view, e.g. those behind a clicked plot point
+    pub selection: Vec<usize>,
+    /// Scroll the first selected row into view, once after the selection changes
+    pub scroll_to_selection: bool,
+    /// Suspect readings with the reasons they were flagged
+    pub outliers: Vec<(usize, String)>,
 }
 
 impl Default for DataTableView {
@@ -13,6 +20,9 @@ impl Default for DataTableView {
             striped: true,
             resizable: true,
             dataset: Vec::new(),
+            selection: Vec::new(),
+            scroll_to_selection: false,
+            outliers: Vec::new(),
         }
     }
 }
@@ -22,6 +32,23 @@ impl DataTableView {
         "☰ Data Table"
     }
 
+    pub fn with_selection(mut self, selection: &[usize], scroll_to_selection: bool) -> Self {
+        self.selection = selection.to_owned();
+        self.scroll_to_selection = scroll_to_selection;
+        self
+    }
+
+    pub fn with_outliers(mut self, screen: &Option<OutlierScreen>) -> Self {
+        if let Some(screen) = screen {
+            self.outliers = screen
+                .outliers
+                .iter()
+                .map(|o| (o.row, o.describe()))
+                .collect();
+        }
+        self
+    }
+
     pub fn show(&mut self, ctx: &egui::Context, dataset: &Vec<Data>, open: &mut bool) {
         self.dataset = dataset.to_owned();
         egui::Window::new(self.name())
@@ -84,6 +111,12 @@ impl DataTableView {
                     .clip(true),
             )
             .min_scrolled_height(0.0);
+        let table = match self.selection.first() {
+            Some(row) if self.scroll_to_selection => {
+                table.scroll_to_row(*row, Some(egui::Align::Center))
+            }
+            _ => table,
+        };
 
         table
             .header(20.0, |mut header| {
@@ -117,8 +150,15 @@ impl DataTableView {
                 for (idx, d) in self.dataset.iter().enumerate() {
                     let row_height = 18.0;
                     body.row(row_height, |mut row| {
+                        row.set_selected(self.selection.contains(&idx));
+                        let outlier = self.outliers.iter().find(|(row, _)| *row == idx);
                         row.col(|ui| {
-                            ui.label(idx.to_string());
+                            match outlier {
+                                Some((_, reasons)) => ui
+                                    .colored_label(egui::Color32::RED, format!("⚠ {idx}"))
+                                    .on_hover_text(reasons),
+                                None => ui.label(idx.to_string()),
+                            };
                         });
                         row.col(|ui| {
                             ui.label(d.part.clone());
@@ -130,7 +170,12 @@ impl DataTableView {
                             ui.label(format!("{}", d.replicate));
                         });
                         row.col(|ui| {
-                            ui.label(format!("{}", d.measured));
+                            match outlier {
+                                Some(_) => {
+                                    ui.colored_label(egui::Color32::RED, format!("{}", d.measured))
+                                }
+                                None => ui.label(format!("{}", d.measured)),
+                            };
                         });
                     });
                 }
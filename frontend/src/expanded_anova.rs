@@ -0,0 +1,368 @@
+use crate::{ExpandedData, statistics::f_test_p_value};
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Model settings for one factor of an expanded gage study
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct FactorSpec {
+    pub name: String,
+    pub random: bool,
+    /// Name of the factor this factor is nested within, if any
+    pub nested_in: Option<String>,
+}
+
+impl FactorSpec {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            random: true,
+            nested_in: None,
+        }
+    }
+}
+
+pub struct AnovaTerm {
+    pub name: String,
+    pub random: bool,
+    pub dof: usize,
+    pub sumsq: f64,
+    pub meansq: f64,
+    pub f: Option<f64>,
+    pub p: Option<f64>,
+    /// Estimated variance component, for random terms only
+    pub varcomp: Option<f64>,
+    own: Vec<usize>,
+    full: BTreeSet<usize>,
+}
+
+impl AnovaTerm {
+    /// True for the part main effect, which is reported as part-to-part variation
+    pub fn is_part(&self) -> bool {
+        self.name == "Part"
+    }
+}
+
+/// Balanced mixed-model ANOVA with arbitrary crossed, nested, random and fixed
+/// factors plus optional 2-way interactions. Expected mean squares follow the
+/// unrestricted model and variance components are estimated by the method of moments.
+pub struct ExpandedAnova {
+    pub terms: Vec<AnovaTerm>,
+    pub dof_repeatability: usize,
+    pub sumsq_repeatability: f64,
+    pub meansq_repeatability: f64,
+    pub dof_total: usize,
+    pub sumsq_total: f64,
+}
+
+impl ExpandedAnova {
+    pub fn from_data(
+        data: &[ExpandedData],
+        factors: &[FactorSpec],
+        interactions: bool,
+    ) -> Result<Self> {
+        if data.is_empty() {
+            bail!("Expanded study requires at least one measurement");
+        }
+        let index = factors
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (f.name.as_str(), i))
+            .collect::<BTreeMap<&str, usize>>();
+        // Ancestors of each factor following the nesting chain
+        let mut ancestors = vec![BTreeSet::new(); factors.len()];
+        for (i, f) in factors.iter().enumerate() {
+            let mut parent = f.nested_in.as_deref();
+            while let Some(name) = parent {
+                let Some(&p) = index.get(name) else {
+                    bail!("Factor {} is nested in unknown factor {name}", f.name);
+                };
+                if p == i || !ancestors[i].insert(p) {
+                    bail!("Factor {} has a circular nesting", f.name);
+                }
+                parent = factors[p].nested_in.as_deref();
+            }
+        }
+
+        // Levels of each factor, counted within its nesting cell
+        let levels = (0..factors.len())
+            .map(|i| {
+                let mut with_self = ancestors[i].clone();
+                with_self.insert(i);
+                let cells = Cells::new(data, factors, &with_self).sums.len();
+                let parent_cells = Cells::new(data, factors, &ancestors[i]).sums.len();
+                cells / parent_cells
+            })
+            .collect::<Vec<usize>>();
+        if let Some(i) = levels.iter().position(|l| *l < 2) {
+            bail!("Factor {} requires at least two levels", factors[i].name);
+        }
+        let all = (0..factors.len()).collect::<BTreeSet<usize>>();
+        let full_cells = Cells::new(data, factors, &all);
+        let expected_cells = levels.iter().product::<usize>();
+        let per_cell = data.len() / expected_cells;
+        if full_cells.sums.len() != expected_cells
+            || full_cells.counts.values().any(|c| *c != per_cell)
+        {
+            bail!("Expanded study requires a balanced design with every factor combination");
+        }
+
+        // Model terms: main effects then 2-way interactions between non-nested factors
+        let mut terms = Vec::new();
+        for (i, f) in factors.iter().enumerate() {
+            let name = match &f.nested_in {
+                Some(parent) => format!("{}({})", f.name, parent),
+                None => f.name.clone(),
+            };
+            terms.push(AnovaTerm::new(name, vec![i], &ancestors, f.random));
+        }
+        if interactions {
+            for i in 0..factors.len() {
+                for j in (i + 1)..factors.len() {
+                    if ancestors[i].contains(&j) || ancestors[j].contains(&i) {
+                        continue;
+                    }
+                    let name = format!("{}*{}", factors[i].name, factors[j].name);
+                    let random = factors[i].random || factors[j].random;
+                    terms.push(AnovaTerm::new(name, vec![i, j], &ancestors, random));
+                }
+            }
+        }
+
+        let n = data.len() as f64;
+        let grand_total = data.iter().map(|d| d.data.measured).sum::<f64>();
+        let correction = grand_total.powi(2) / n;
+        let sumsq_total = data.iter().map(|d| d.data.measured.powi(2)).sum::<f64>() - correction;
+        let mut marginal: BTreeMap<Vec<usize>, f64> = BTreeMap::new();
+        for term in terms.iter_mut() {
+            // Inclusion-exclusion over the term's own factors, keeping its nesting parents
+            let parents = term
+                .full
+                .iter()
+                .filter(|f| !term.own.contains(f))
+                .copied()
+                .collect::<BTreeSet<usize>>();
+            let mut sumsq = 0.0;
+            for mask in 0..(1usize << term.own.len()) {
+                let mut subset = parents.clone();
+                let mut size = 0;
+                for (bit, f) in term.own.iter().enumerate() {
+                    if mask & (1 << bit) != 0 {
+                        subset.insert(*f);
+                        size += 1;
+                    }
+                }
+                let key = subset.iter().copied().collect::<Vec<usize>>();
+                let m = *marginal
+                    .entry(key)
+                    .or_insert_with(|| Cells::new(data, factors, &subset).marginal_sumsq());
+                let sign = if (term.own.len() - size) % 2 == 0 {
+                    1.0
+                } else {
+                    -1.0
+                };
+                sumsq += sign * m;
+            }
+            term.dof = term.own.iter().map(|f| levels[*f] - 1).product::<usize>()
+                * parents.iter().map(|f| levels[*f]).product::<usize>();
+            term.sumsq = sumsq;
+            term.meansq = sumsq / term.dof as f64;
+        }
+        let dof_total = data.len() - 1;
+        let model_dof = terms.iter().map(|t| t.dof).sum::<usize>();
+        if model_dof >= dof_total {
+            bail!("Expanded study has no degrees of freedom left for repeatability");
+        }
+        let dof_repeatability = dof_total - model_dof;
+        let sumsq_repeatability = sumsq_total - terms.iter().map(|t| t.sumsq).sum::<f64>();
+        let meansq_repeatability = sumsq_repeatability / dof_repeatability as f64;
+
+        // Observations per cell of each term, the coefficient of its variance component
+        let coefficients = terms
+            .iter()
+            .map(|t| n / t.full.iter().map(|f| levels[*f]).product::<usize>() as f64)
+            .collect::<Vec<f64>>();
+        // Random terms contributing to each term's expected mean square
+        let ems = terms
+            .iter()
+            .map(|t| {
+                terms
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, u)| u.random && u.full.is_superset(&t.full))
+                    .map(|(k, _)| k)
+                    .collect::<BTreeSet<usize>>()
+            })
+            .collect::<Vec<BTreeSet<usize>>>();
+
+        // Variance components, solved from the largest terms down
+        let mut order = (0..terms.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|k| std::cmp::Reverse(terms[*k].full.len()));
+        for k in order.into_iter() {
+            if !terms[k].random {
+                continue;
+            }
+            let others = ems[k]
+                .iter()
+                .filter(|u| **u != k)
+                .map(|u| coefficients[*u] * terms[*u].varcomp.unwrap_or(0.0))
+                .sum::<f64>();
+            terms[k].varcomp =
+                Some((terms[k].meansq - meansq_repeatability - others) / coefficients[k]);
+        }
+
+        // F tests against the term whose expected mean square matches the null hypothesis
+        let denominators = (0..terms.len())
+            .map(|k| {
+                let mut null = ems[k].clone();
+                null.remove(&k);
+                if null.is_empty() {
+                    Some((meansq_repeatability, dof_repeatability))
+                } else {
+                    (0..terms.len())
+                        .find(|u| *u != k && ems[*u] == null)
+                        .map(|u| (terms[u].meansq, terms[u].dof))
+                }
+            })
+            .collect::<Vec<Option<(f64, usize)>>>();
+        for (term, denominator) in terms.iter_mut().zip(denominators) {
+            if let Some((meansq, dof)) = denominator {
+                let f = term.meansq / meansq;
+                term.f = Some(f);
+                term.p = Some(f_test_p_value(f, term.dof as f64, dof as f64));
+            }
+        }
+
+        Ok(Self {
+            terms,
+            dof_repeatability,
+            sumsq_repeatability,
+            meansq_repeatability,
+            dof_total,
+            sumsq_total,
+        })
+    }
+}
+
+impl AnovaTerm {
+    fn new(name: String, own: Vec<usize>, ancestors: &[BTreeSet<usize>], random: bool) -> Self {
+        let mut full = own.iter().copied().collect::<BTreeSet<usize>>();
+        for f in own.iter() {
+            full.extend(ancestors[*f].iter().copied());
+        }
+        Self {
+            name,
+            random,
+            dof: 0,
+            sumsq: 0.0,
+            meansq: 0.0,
+            f: None,
+            p: None,
+            varcomp: None,
+            own,
+            full,
+        }
+    }
+}
+
+/// Sums and counts of the measurements in each cell of a set of factors
+struct Cells<'a> {
+    sums: BTreeMap<Vec<&'a str>, f64>,
+    counts: BTreeMap<Vec<&'a str>, usize>,
+}
+
+impl<'a> Cells<'a> {
+    fn new(data: &'a [ExpandedData], factors: &[FactorSpec], set: &BTreeSet<usize>) -> Self {
+        let mut sums = BTreeMap::new();
+        let mut counts = BTreeMap::new();
+        for d in data.iter() {
+            let key = set
+                .iter()
+                .map(|f| d.level(&factors[*f].name))
+                .collect::<Vec<&str>>();
+            *sums.entry(key.clone()).or_insert(0.0) += d.data.measured;
+            *counts.entry(key).or_insert(0) += 1;
+        }
+        Self { sums, counts }
+    }
+
+    /// Sum over cells of (cell total)^2 / (cell count)
+    fn marginal_sumsq(&self) -> f64 {
+        self.sums
+            .iter()
+            .map(|(key, sum)| sum.powi(2) / self.counts[key] as f64)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{VarianceComponents, two_by_two_data};
+
+    /// The shared two-by-two readings without additional factors
+    fn data() -> Vec<ExpandedData> {
+        two_by_two_data()
+            .into_iter()
+            .map(|data| ExpandedData {
+                data,
+                factors: Default::default(),
+            })
+            .collect()
+    }
+
+    fn term<'a>(anova: &'a ExpandedAnova, name: &str) -> &'a AnovaTerm {
+        anova.terms.iter().find(|t| t.name == name).unwrap()
+    }
+
+    #[test]
+    fn crossed_design_matches_two_way_anova() {
+        // Mean squares: parts 72, operators 18, interaction 8, repeatability 2
+        let factors = [FactorSpec::new("Part"), FactorSpec::new("Operator")];
+        let anova = ExpandedAnova::from_data(&data(), &factors, true).unwrap();
+        assert!((term(&anova, "Part").meansq - 72.0).abs() < 1e-9);
+        assert!((term(&anova, "Operator").meansq - 18.0).abs() < 1e-9);
+        assert!((term(&anova, "Part*Operator").meansq - 8.0).abs() < 1e-9);
+        assert!((anova.meansq_repeatability - 2.0).abs() < 1e-9);
+        // Main effects are tested against the interaction, which is tested against error
+        assert!((term(&anova, "Part").f.unwrap() - 9.0).abs() < 1e-9);
+        assert!((term(&anova, "Operator").f.unwrap() - 2.25).abs() < 1e-9);
+        assert!((term(&anova, "Part*Operator").f.unwrap() - 4.0).abs() < 1e-9);
+        // Expected mean squares of the random model
+        assert!((term(&anova, "Part").varcomp.unwrap() - 16.0).abs() < 1e-9);
+        assert!((term(&anova, "Operator").varcomp.unwrap() - 2.5).abs() < 1e-9);
+        assert!((term(&anova, "Part*Operator").varcomp.unwrap() - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn nested_design_matches_nested_anova() {
+        let mut part = FactorSpec::new("Part");
+        part.nested_in = Some("Operator".to_owned());
+        let factors = [FactorSpec::new("Operator"), part];
+        let anova = ExpandedAnova::from_data(&data(), &factors, true).unwrap();
+        assert_eq!(anova.terms.len(), 2);
+        assert!((term(&anova, "Part(Operator)").sumsq - 80.0).abs() < 1e-9);
+        assert!((term(&anova, "Part(Operator)").varcomp.unwrap() - 19.0).abs() < 1e-9);
+        assert!((term(&anova, "Operator").varcomp.unwrap() + 5.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fixed_terms_are_listed_outside_gage_rr() {
+        let mut operator = FactorSpec::new("Operator");
+        operator.random = false;
+        let factors = [FactorSpec::new("Part"), operator];
+        let anova = ExpandedAnova::from_data(&data(), &factors, true).unwrap();
+        assert!(term(&anova, "Operator").varcomp.is_none());
+        let components = VarianceComponents::from_expanded_anova(&anova);
+        assert_eq!(components.fixed_terms, vec!["Operator".to_owned()]);
+        assert!((components.total_gagerr.reproducibility.varcomp - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unbalanced_design_is_rejected() {
+        let mut data = data();
+        data.pop();
+        let factors = [FactorSpec::new("Part"), FactorSpec::new("Operator")];
+        assert!(ExpandedAnova::from_data(&data, &factors, true).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use anyhow::{Result, bail};
+use gage_study::data::Data;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Columns that make up a `Data` record; every other column is an additional factor
+const DATA_COLUMNS: [&str; 6] = [
+    "name",
+    "part",
+    "operator",
+    "replicate",
+    "measured",
+    "nominal",
+];
+
+/// A measurement with additional factor columns (fixture, gage, lab, ...)
+/// beyond part and operator.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ExpandedData {
+    pub data: Data,
+    pub factors: BTreeMap<String, String>,
+}
+
+impl ExpandedData {
+    pub fn from_raw(raw: &[u8], ext: &str) -> Result<Vec<Self>> {
+        match ext.to_lowercase().as_str() {
+            "json" => Self::from_raw_json(raw),
+            "csv" => Self::from_raw_csv(raw),
+            _ => bail!("Unsupported file extension: {ext}"),
+        }
+    }
+
+    pub fn from_raw_json(raw: &[u8]) -> Result<Vec<Self>> {
+        let records: Vec<serde_json::Map<String, serde_json::Value>> = serde_json::from_slice(raw)?;
+        records.into_iter().map(Self::from_map).collect()
+    }
+
+    pub fn from_raw_csv(raw: &[u8]) -> Result<Vec<Self>> {
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(raw);
+        let headers = reader.headers()?.clone();
+        let mut data = Vec::new();
+        for record in reader.records() {
+            let record = record?;
+            let mut map = serde_json::Map::new();
+            for (header, field) in headers.iter().zip(record.iter()) {
+                if header.is_empty() {
+                    continue;
+                }
+                let value = match header {
+                    "replicate" => serde_json::Value::from(field.parse::<u64>()?),
+                    "measured" | "nominal" => serde_json::Value::from(field.parse::<f64>()?),
+                    _ => serde_json::Value::from(field),
+                };
+                map.insert(header.to_owned(), value);
+            }
+            data.push(Self::from_map(map)?);
+        }
+        Ok(data)
+    }
+
+    /// Names of the additional factor columns present in `data`
+    pub fn factor_names(data: &[Self]) -> Vec<String> {
+        let mut names = data
+            .iter()
+            .flat_map(|d| d.factors.keys().cloned())
+            .collect::<Vec<String>>();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Level of the named factor; `Part` and `Operator` refer to the `Data` fields
+    pub fn level(&self, factor: &str) -> &str {
+        match factor {
+            "Part" => &self.data.part,
+            "Operator" => &self.data.operator,
+            _ => self.factors.get(factor).map_or("", String::as_str),
+        }
+    }
+
+    fn from_map(map: serde_json::Map<String, serde_json::Value>) -> Result<Self> {
+        let mut factors = BTreeMap::new();
+        let mut fields = serde_json::Map::new();
+        for (key, value) in map.into_iter() {
+            if DATA_COLUMNS.contains(&key.as_str()) {
+                fields.insert(key, value);
+            } else {
+                let level = match value {
+                    serde_json::Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                factors.insert(key, level);
+            }
+        }
+        Ok(Self {
+            data: serde_json::from_value(serde_json::Value::Object(fields))?,
+            factors,
+        })
+    }
+}
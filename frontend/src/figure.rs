@@ -0,0 +1,886 @@
+use crate::escape_markup;
+use anyhow::{Context, Result};
+use eframe::egui;
+use egui_plot::{
+    Bar, BarChart, BoxElem, BoxPlot, BoxSpread, HLine, Legend, Line, LineStyle, MarkerShape, Plot,
+    PlotPoint, Points, Polygon,
+};
+use gage_study::data::Data;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+
+pub enum Marker {
+    Circle,
+    Square,
+}
+
+/// Five-number summary drawn as a box and whiskers at `x`
+pub struct BoxSummary {
+    pub x: f64,
+    pub width: f64,
+    pub min: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub max: f64,
+    pub name: String,
+}
+
+pub struct BarSummary {
+    pub x: f64,
+    pub height: f64,
+    pub width: f64,
+    pub name: String,
+}
+
+pub enum Mark {
+    Points {
+        points: Vec<[f64; 2]>,
+        marker: Marker,
+        radius: f32,
+        filled: bool,
+        highlight: bool,
+        /// Rows of the readings behind each point, empty for points not drawn
+        /// from readings
+        rows: Vec<Vec<usize>>,
+    },
+    Line {
+        points: Vec<[f64; 2]>,
+        highlight: bool,
+        dashed: bool,
+    },
+    HLine {
+        y: f64,
+        width: f32,
+        dashed: bool,
+    },
+    /// Shaded horizontal band between `lower` and `upper` across the plot
+    Band {
+        lower: f64,
+        upper: f64,
+    },
+    Bars(Vec<BarSummary>),
+    Boxes(Vec<BoxSummary>),
+}
+
+pub struct Series {
+    /// Legend entry; series with the same name share it
+    pub name: String,
+    pub color: egui::Color32,
+    pub mark: Mark,
+}
+
+/// Description of a chart, drawn on screen with `egui_plot` or rendered off
+/// screen to SVG and PNG for reports
+#[derive(Default)]
+pub struct Figure {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    /// Names of x positions for a categorical axis, numbers are shown when empty
+    pub x_ticks: Vec<(f64, String)>,
+    /// Labels of x positions for tooltips of points not drawn from readings,
+    /// such as timestamps
+    pub x_labels: Vec<(f64, String)>,
+    /// Plots sharing this group move and zoom together along x
+    pub linked_x: Option<String>,
+    pub series: Vec<Series>,
+    /// Height on screen, the available height when not given
+    pub height: Option<f32>,
+}
+
+impl Figure {
+    pub fn new(title: &str, x_label: &str, y_label: &str) -> Self {
+        Self {
+            title: title.to_owned(),
+            x_label: x_label.to_owned(),
+            y_label: y_label.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_x_ticks(mut self, x_ticks: Vec<(f64, String)>) -> Self {
+        self.x_ticks = x_ticks;
+        self
+    }
+
+    pub fn with_x_labels(mut self, x_labels: Vec<(f64, String)>) -> Self {
+        self.x_labels = x_labels;
+        self
+    }
+
+    pub fn with_linked_x(mut self, group: &str) -> Self {
+        self.linked_x = Some(group.to_owned());
+        self
+    }
+
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    pub fn push(&mut self, name: impl ToString, color: egui::Color32, mark: Mark) {
+        self.series.push(Series {
+            name: name.to_string(),
+            color,
+            mark,
+        });
+    }
+
+    /// Shows the figure with tooltips of the readings in `data` behind each point
+    /// and the points of the rows in `selection` highlighted. Clicking a point or
+    /// dragging a box with shift held returns the rows under it as the new
+    /// selection.
+    pub fn show(
+        &self,
+        ui: &mut egui::Ui,
+        id: &str,
+        data: &[Data],
+        selection: &[usize],
+    ) -> Option<Vec<usize>> {
+        let brush_id = egui::Id::new((id, "brush"));
+        let brush_start = ui.data(|d| d.get_temp::<PlotPoint>(brush_id));
+        let shift = ui.input(|i| i.modifiers.shift);
+        let plot = Plot::new(id)
+            .legend(Legend::default())
+            .x_axis_label(self.x_label.as_str())
+            .y_axis_label(self.y_label.as_str())
+            .allow_drag(!shift && brush_start.is_none())
+            .label_formatter(|name, value| self.tooltip(data, name, value))
+            .set_margin_fraction(egui::Vec2 { x: 0.1, y: 0.1 });
+        let plot = match self.height {
+            Some(height) => plot.height(height),
+            None => plot,
+        };
+        let plot = match &self.linked_x {
+            Some(group) => plot.link_axis(egui::Id::new(group), [true, false]),
+            None => plot,
+        };
+        let plot = if self.x_ticks.is_empty() {
+            plot
+        } else {
+            let ticks = self.x_ticks.clone();
+            plot.x_axis_formatter(move |x, _range: &RangeInclusive<f64>| {
+                ticks
+                    .iter()
+                    .find(|(position, _)| (x.value - position).abs() < 1e-6)
+                    .map_or(String::new(), |(_, label)| label.clone())
+            })
+        };
+        let selected = self
+            .reading_points()
+            .filter(|(_, rows)| rows.iter().any(|row| selection.contains(row)))
+            .map(|(point, _)| point)
+            .collect::<Vec<[f64; 2]>>();
+        let ((x_min, x_max), _) = self.bounds();
+        let response = plot.show(ui, |plot_ui| {
+            for series in self.series.iter() {
+                let name = series.name.as_str();
+                match &series.mark {
+                    Mark::Points {
+                        points,
+                        marker,
+                        radius,
+                        filled,
+                        highlight,
+                        ..
+                    } => plot_ui.points(
+                        Points::new(name, points.clone())
+                            .shape(match marker {
+                                Marker::Circle => MarkerShape::Circle,
+                                Marker::Square => MarkerShape::Square,
+                            })
+                            .radius(*radius)
+                            .filled(*filled)
+                            .highlight(*highlight)
+                            .color(series.color),
+                    ),
+                    Mark::Line {
+                        points,
+                        highlight,
+                        dashed,
+                    } => plot_ui.line(
+                        Line::new(name, points.clone())
+                            .color(series.color)
+                            .highlight(*highlight)
+                            .style(line_style(*dashed)),
+                    ),
+                    Mark::HLine { y, width, dashed } => plot_ui.hline(
+                        HLine::new(name, *y)
+                            .color(series.color)
+                            .width(*width)
+                            .style(line_style(*dashed)),
+                    ),
+                    Mark::Band { lower, upper } => plot_ui.polygon(
+                        Polygon::new(
+                            name,
+                            vec![
+                                [x_min, *lower],
+                                [x_max, *lower],
+                                [x_max, *upper],
+                                [x_min, *upper],
+                            ],
+                        )
+                        .fill_color(series.color.gamma_multiply(0.15))
+                        .stroke(egui::Stroke::NONE),
+                    ),
+                    Mark::Bars(bars) => plot_ui.bar_chart(
+                        BarChart::new(
+                            name,
+                            bars.iter()
+                                .map(|b| Bar::new(b.x, b.height).width(b.width).name(&b.name))
+                                .collect(),
+                        )
+                        .color(series.color),
+                    ),
+                    Mark::Boxes(boxes) => plot_ui.box_plot(
+                        BoxPlot::new(
+                            name,
+                            boxes
+                                .iter()
+                                .map(|b| {
+                                    BoxElem::new(
+                                        b.x,
+                                        BoxSpread::new(b.min, b.q1, b.median, b.q3, b.max),
+                                    )
+                                    .box_width(b.width)
+                                    .name(&b.name)
+                                })
+                                .collect(),
+                        )
+                        .color(series.color),
+                    ),
+                }
+            }
+            if !selected.is_empty() {
+                plot_ui.points(
+                    Points::new("selected", selected)
+                        .shape(MarkerShape::Circle)
+                        .radius(7.0)
+                        .filled(false)
+                        .color(egui::Color32::GOLD),
+                );
+            }
+            if let (Some(start), Some(end)) = (brush_start, plot_ui.pointer_coordinate()) {
+                plot_ui.polygon(
+                    Polygon::new(
+                        "",
+                        vec![
+                            [start.x, start.y],
+                            [end.x, start.y],
+                            [end.x, end.y],
+                            [start.x, end.y],
+                        ],
+                    )
+                    .stroke(egui::Stroke::new(1.0, egui::Color32::GOLD)),
+                );
+            }
+        });
+
+        let transform = response.transform;
+        let pointer = response
+            .response
+            .interact_pointer_pos()
+            .or(response.response.hover_pos());
+        if shift && brush_start.is_none() && response.response.drag_started() {
+            if let Some(pointer) = pointer {
+                let start = transform.value_from_position(pointer);
+                ui.data_mut(|d| d.insert_temp(brush_id, start));
+            }
+            return None;
+        }
+        if let Some(start) = brush_start {
+            if !response.response.drag_stopped() {
+                return None;
+            }
+            ui.data_mut(|d| d.remove::<PlotPoint>(brush_id));
+            let end = transform.value_from_position(pointer?);
+            let (x_min, x_max) = (start.x.min(end.x), start.x.max(end.x));
+            let (y_min, y_max) = (start.y.min(end.y), start.y.max(end.y));
+            let mut rows = self
+                .reading_points()
+                .filter(|([x, y], _)| *x >= x_min && *x <= x_max && *y >= y_min && *y <= y_max)
+                .flat_map(|(_, rows)| rows.iter().copied())
+                .collect::<Vec<usize>>();
+            rows.sort();
+            rows.dedup();
+            return Some(rows);
+        }
+        if !response.response.clicked() {
+            return None;
+        }
+        // The readings of the point nearest the click, none when it misses them all
+        let pointer = pointer?;
+        let nearest = self
+            .reading_points()
+            .map(|([x, y], rows)| {
+                let position = transform.position_from_point(&PlotPoint::new(x, y));
+                (position.distance(pointer), rows)
+            })
+            .filter(|(distance, _)| *distance < 10.0)
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+        Some(nearest.map_or(Vec::new(), |(_, rows)| rows.clone()))
+    }
+
+    /// Points drawn from readings, with the rows of the readings behind each
+    fn reading_points(&self) -> impl Iterator<Item = ([f64; 2], &Vec<usize>)> {
+        self.series.iter().flat_map(|series| match &series.mark {
+            Mark::Points { points, rows, .. } => points
+                .iter()
+                .copied()
+                .zip(rows.iter())
+                .filter(|(_, rows)| !rows.is_empty())
+                .collect::<Vec<([f64; 2], &Vec<usize>)>>(),
+            _ => Vec::new(),
+        })
+    }
+
+    /// Hover label: the readings behind a point of series `name` at `value`, or
+    /// the coordinates
+    fn tooltip(&self, data: &[Data], name: &str, value: &PlotPoint) -> String {
+        let mut rows = self
+            .series
+            .iter()
+            .filter(|series| series.name == name)
+            .flat_map(|series| match &series.mark {
+                Mark::Points { points, rows, .. } => points
+                    .iter()
+                    .zip(rows.iter())
+                    .filter(|(point, _)| point[0] == value.x && point[1] == value.y)
+                    .flat_map(|(_, rows)| rows.iter().copied())
+                    .collect::<Vec<usize>>(),
+                _ => Vec::new(),
+            })
+            .filter(|row| *row < data.len())
+            .collect::<Vec<usize>>();
+        rows.sort();
+        rows.dedup();
+        let prefix = if name.is_empty() {
+            String::new()
+        } else {
+            format!("{name}\n")
+        };
+        let nearest_label = self
+            .x_labels
+            .iter()
+            .min_by(|a, b| (a.0 - value.x).abs().total_cmp(&(b.0 - value.x).abs()));
+        match (rows.as_slice(), nearest_label) {
+            ([], Some((_, label))) => format!("{prefix}{label}\n{:.5}", value.y),
+            ([], None) => format!("{prefix}x = {:.4}\ny = {:.4}", value.x, value.y),
+            ([row], _) => {
+                let d = &data[*row];
+                format!(
+                    "{prefix}{}\nRow: {row}\nPart: {}\nOperator: {}\nReplicate: {}\nMeasured: {}",
+                    d.name, d.part, d.operator, d.replicate, d.measured
+                )
+            }
+            (rows, _) => {
+                const SHOWN: usize = 10;
+                let mut lines = rows
+                    .iter()
+                    .take(SHOWN)
+                    .map(|row| {
+                        let d = &data[*row];
+                        format!(
+                            "{row}: {} part {} operator {} replicate {} = {}",
+                            d.name, d.part, d.operator, d.replicate, d.measured
+                        )
+                    })
+                    .collect::<Vec<String>>();
+                if rows.len() > SHOWN {
+                    lines.push(format!("… {} more", rows.len() - SHOWN));
+                }
+                format!("{prefix}{}", lines.join("\n"))
+            }
+        }
+    }
+
+    /// Data range covered by the series and x ticks, as ((x min, x max), (y min, y max))
+    fn bounds(&self) -> ((f64, f64), (f64, f64)) {
+        let mut x = (f64::INFINITY, f64::NEG_INFINITY);
+        let mut y = (f64::INFINITY, f64::NEG_INFINITY);
+        let include = |range: &mut (f64, f64), value: f64| {
+            if value.is_finite() {
+                range.0 = range.0.min(value);
+                range.1 = range.1.max(value);
+            }
+        };
+        for (position, _) in self.x_ticks.iter() {
+            include(&mut x, *position);
+        }
+        for series in self.series.iter() {
+            match &series.mark {
+                Mark::Points { points, .. } | Mark::Line { points, .. } => {
+                    for [px, py] in points.iter() {
+                        include(&mut x, *px);
+                        include(&mut y, *py);
+                    }
+                }
+                Mark::HLine { y: value, .. } => include(&mut y, *value),
+                Mark::Band { lower, upper } => {
+                    include(&mut y, *lower);
+                    include(&mut y, *upper);
+                }
+                Mark::Bars(bars) => {
+                    for b in bars.iter() {
+                        include(&mut x, b.x - b.width / 2.0);
+                        include(&mut x, b.x + b.width / 2.0);
+                        include(&mut y, 0.0);
+                        include(&mut y, b.height);
+                    }
+                }
+                Mark::Boxes(boxes) => {
+                    for b in boxes.iter() {
+                        include(&mut x, b.x - b.width / 2.0);
+                        include(&mut x, b.x + b.width / 2.0);
+                        include(&mut y, b.min);
+                        include(&mut y, b.max);
+                    }
+                }
+            }
+        }
+        let pad = |(min, max): (f64, f64)| {
+            if !min.is_finite() {
+                (0.0, 1.0)
+            } else if max - min <= f64::EPSILON * max.abs().max(1.0) {
+                (min - 0.5, max + 0.5)
+            } else {
+                let margin = (max - min) * 0.1;
+                (min - margin, max + margin)
+            }
+        };
+        (pad(x), pad(y))
+    }
+
+    /// Renders the figure to an SVG document of `settings.width` × `settings.height`
+    /// points, with the title and axis labels from `settings` when given
+    pub fn to_svg(&self, settings: &ExportSettings) -> String {
+        let width = settings.width as f64;
+        let height = settings.height as f64;
+        let title = settings.title_or(&self.title);
+        let x_label = settings.x_label_or(&self.x_label);
+        let y_label = settings.y_label_or(&self.y_label);
+        let legend = self.legend();
+        let legend_width = if legend.is_empty() {
+            0.0
+        } else {
+            legend
+                .iter()
+                .map(|(name, _)| name.chars().count())
+                .max()
+                .unwrap_or(0) as f64
+                * 7.0
+                + 40.0
+        };
+        let (left, right) = (80.0, width - 20.0 - legend_width);
+        let (top, bottom) = (if title.is_empty() { 20.0 } else { 50.0 }, height - 60.0);
+        let ((x_min, x_max), (y_min, y_max)) = self.bounds();
+        let sx = |x: f64| left + (x - x_min) / (x_max - x_min) * (right - left);
+        let sy = |y: f64| bottom - (y - y_min) / (y_max - y_min) * (bottom - top);
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif" font-size="12">"#
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect width="{width}" height="{height}" fill="white"/>"#
+        );
+        let _ = writeln!(
+            svg,
+            r#"<defs><clipPath id="plot-area"><rect x="{left}" y="{top}" width="{}" height="{}"/></clipPath></defs>"#,
+            right - left,
+            bottom - top
+        );
+
+        // Grid and tick labels
+        for y in ticks(y_min, y_max) {
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{left}" y1="{0:.2}" x2="{right}" y2="{0:.2}" stroke="#e0e0e0"/><text x="{1}" y="{0:.2}" text-anchor="end" dominant-baseline="middle">{2}</text>"##,
+                sy(y),
+                left - 6.0,
+                format_tick(y)
+            );
+        }
+        let x_ticks = if self.x_ticks.is_empty() {
+            ticks(x_min, x_max)
+                .into_iter()
+                .map(|x| (x, format_tick(x)))
+                .collect()
+        } else {
+            self.x_ticks.clone()
+        };
+        for (x, label) in x_ticks.iter().filter(|(x, _)| *x >= x_min && *x <= x_max) {
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{0:.2}" y1="{top}" x2="{0:.2}" y2="{bottom}" stroke="#e0e0e0"/><text x="{0:.2}" y="{1}" text-anchor="middle">{2}</text>"##,
+                sx(*x),
+                bottom + 16.0,
+                escape_markup(label)
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{left}" y="{top}" width="{}" height="{}" fill="none" stroke="black"/>"#,
+            right - left,
+            bottom - top
+        );
+
+        let _ = writeln!(svg, r#"<g clip-path="url(#plot-area)">"#);
+        for series in self.series.iter() {
+            let color = svg_color(series.color);
+            match &series.mark {
+                Mark::Points {
+                    points,
+                    marker,
+                    radius,
+                    filled,
+                    ..
+                } => {
+                    let paint = if *filled {
+                        format!(r#"fill="{color}""#)
+                    } else {
+                        format!(r#"fill="none" stroke="{color}" stroke-width="1.5""#)
+                    };
+                    for [x, y] in points.iter() {
+                        let (x, y, r) = (sx(*x), sy(*y), *radius as f64);
+                        let _ = match marker {
+                            Marker::Circle => writeln!(
+                                svg,
+                                r#"<circle cx="{x:.2}" cy="{y:.2}" r="{r}" {paint}/>"#
+                            ),
+                            Marker::Square => writeln!(
+                                svg,
+                                r#"<rect x="{:.2}" y="{:.2}" width="{}" height="{}" {paint}/>"#,
+                                x - r,
+                                y - r,
+                                2.0 * r,
+                                2.0 * r
+                            ),
+                        };
+                    }
+                }
+                Mark::Line {
+                    points,
+                    highlight,
+                    dashed,
+                } => {
+                    let path = points
+                        .iter()
+                        .map(|[x, y]| format!("{:.2},{:.2}", sx(*x), sy(*y)))
+                        .collect::<Vec<String>>()
+                        .join(" ");
+                    let _ = writeln!(
+                        svg,
+                        r#"<polyline points="{path}" fill="none" stroke="{color}" stroke-width="{}"{}/>"#,
+                        if *highlight { 2.0 } else { 1.5 },
+                        svg_dash(*dashed)
+                    );
+                }
+                Mark::HLine { y, width, dashed } => {
+                    let _ = writeln!(
+                        svg,
+                        r#"<line x1="{left}" y1="{0:.2}" x2="{right}" y2="{0:.2}" stroke="{color}" stroke-width="{width}"{1}/>"#,
+                        sy(*y),
+                        svg_dash(*dashed)
+                    );
+                }
+                Mark::Band { lower, upper } => {
+                    let _ = writeln!(
+                        svg,
+                        r#"<rect x="{left}" y="{:.2}" width="{}" height="{:.2}" fill="{color}" fill-opacity="0.15"/>"#,
+                        sy(*upper),
+                        right - left,
+                        sy(*lower) - sy(*upper)
+                    );
+                }
+                Mark::Bars(bars) => {
+                    for b in bars.iter() {
+                        let (x0, x1) = (sx(b.x - b.width / 2.0), sx(b.x + b.width / 2.0));
+                        let (y0, y1) = (sy(b.height.max(0.0)), sy(b.height.min(0.0)));
+                        let _ = writeln!(
+                            svg,
+                            r#"<rect x="{x0:.2}" y="{y0:.2}" width="{:.2}" height="{:.2}" fill="{color}" fill-opacity="0.5" stroke="{color}"/>"#,
+                            x1 - x0,
+                            y1 - y0
+                        );
+                    }
+                }
+                Mark::Boxes(boxes) => {
+                    for b in boxes.iter() {
+                        let (x0, xc, x1) =
+                            (sx(b.x - b.width / 2.0), sx(b.x), sx(b.x + b.width / 2.0));
+                        let _ = writeln!(
+                            svg,
+                            r#"<g stroke="{color}" stroke-width="1.5"><rect x="{x0:.2}" y="{q3:.2}" width="{width:.2}" height="{height:.2}" fill="{color}" fill-opacity="0.2"/><line x1="{x0:.2}" y1="{median:.2}" x2="{x1:.2}" y2="{median:.2}"/><line x1="{xc:.2}" y1="{q3:.2}" x2="{xc:.2}" y2="{max:.2}"/><line x1="{xc:.2}" y1="{q1:.2}" x2="{xc:.2}" y2="{min:.2}"/></g>"#,
+                            q3 = sy(b.q3),
+                            q1 = sy(b.q1),
+                            width = x1 - x0,
+                            height = sy(b.q1) - sy(b.q3),
+                            median = sy(b.median),
+                            max = sy(b.max),
+                            min = sy(b.min),
+                        );
+                    }
+                }
+            }
+        }
+        let _ = writeln!(svg, "</g>");
+
+        // Labels and legend
+        if !title.is_empty() {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="30" text-anchor="middle" font-size="16" font-weight="bold">{}</text>"#,
+                (left + right) / 2.0,
+                escape_markup(title)
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+            (left + right) / 2.0,
+            height - 20.0,
+            escape_markup(x_label)
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text transform="translate(20 {}) rotate(-90)" text-anchor="middle">{}</text>"#,
+            (top + bottom) / 2.0,
+            escape_markup(y_label)
+        );
+        for (i, (name, color)) in legend.iter().enumerate() {
+            let y = top + 10.0 + i as f64 * 18.0;
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="12" height="12" fill="{}"/><text x="{}" y="{}" dominant-baseline="middle">{}</text>"#,
+                right + 12.0,
+                y - 6.0,
+                svg_color(*color),
+                right + 30.0,
+                y,
+                escape_markup(name)
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Renders the figure to a PNG of `settings.width` × `settings.height`
+    /// points, scaled by `settings.scale` for print resolution
+    pub fn to_png(&self, settings: &ExportSettings) -> Result<Vec<u8>> {
+        use resvg::{tiny_skia, usvg};
+        let svg = self.to_svg(settings);
+        // Text is set in egui's own font, so the image does not depend on system fonts
+        let mut options = usvg::Options::default();
+        let fonts = egui::FontDefinitions::default();
+        let font = fonts
+            .families
+            .get(&egui::FontFamily::Proportional)
+            .and_then(|names| names.first())
+            .and_then(|name| fonts.font_data.get(name))
+            .context("No proportional font to render text with")?;
+        let fontdb = options.fontdb_mut();
+        fontdb.load_font_data(font.font.to_vec());
+        if let Some(family) = fontdb
+            .faces()
+            .next()
+            .and_then(|face| face.families.first())
+            .map(|(family, _)| family.clone())
+        {
+            fontdb.set_sans_serif_family(family);
+        }
+        let tree = usvg::Tree::from_str(&svg, &options)?;
+        let scale = settings.scale.max(0.1);
+        let mut pixmap = tiny_skia::Pixmap::new(
+            (settings.width as f32 * scale).round() as u32,
+            (settings.height as f32 * scale).round() as u32,
+        )
+        .context("Invalid image size")?;
+        resvg::render(
+            &tree,
+            tiny_skia::Transform::from_scale(scale, scale),
+            &mut pixmap.as_mut(),
+        );
+        Ok(pixmap.encode_png()?)
+    }
+
+    /// Distinct legend entries in drawing order, with the color of their first series
+    fn legend(&self) -> Vec<(&str, egui::Color32)> {
+        let mut legend = Vec::<(&str, egui::Color32)>::new();
+        for series in self.series.iter() {
+            if !series.name.is_empty() && !legend.iter().any(|(name, _)| *name == series.name) {
+                legend.push((series.name.as_str(), series.color));
+            }
+        }
+        legend
+    }
+}
+
+fn line_style(dashed: bool) -> LineStyle {
+    if dashed {
+        LineStyle::dashed_loose()
+    } else {
+        LineStyle::Solid
+    }
+}
+
+/// Dash attribute of an SVG stroke
+fn svg_dash(dashed: bool) -> &'static str {
+    if dashed {
+        r#" stroke-dasharray="8 6""#
+    } else {
+        ""
+    }
+}
+
+/// Size and labels of exported images, kept in egui's persisted memory per plot
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    pub width: u32,
+    pub height: u32,
+    /// Pixels per point of the PNG
+    pub scale: f32,
+    /// Replace the figure's title and axis labels when not empty
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 500,
+            scale: 2.0,
+            title: String::new(),
+            x_label: String::new(),
+            y_label: String::new(),
+        }
+    }
+}
+
+impl ExportSettings {
+    fn title_or<'a>(&'a self, title: &'a str) -> &'a str {
+        if self.title.is_empty() {
+            title
+        } else {
+            &self.title
+        }
+    }
+
+    fn x_label_or<'a>(&'a self, label: &'a str) -> &'a str {
+        if self.x_label.is_empty() {
+            label
+        } else {
+            &self.x_label
+        }
+    }
+
+    fn y_label_or<'a>(&'a self, label: &'a str) -> &'a str {
+        if self.y_label.is_empty() {
+            label
+        } else {
+            &self.y_label
+        }
+    }
+
+    /// Shows the export settings of plot `id` with PNG and SVG buttons, and
+    /// returns the file name and content of a requested export
+    pub fn ui(ui: &mut egui::Ui, id: &str, figure: &Figure) -> Option<(String, Vec<u8>)> {
+        let id = egui::Id::new(("export_settings", id));
+        let mut settings = ui
+            .data_mut(|d| d.get_persisted::<Self>(id))
+            .unwrap_or_default();
+        let mut export = None;
+        egui::CollapsingHeader::new("Export")
+            .id_salt(id)
+            .show(ui, |ui| {
+                egui::Grid::new(id.with("grid")).show(ui, |ui| {
+                    ui.label("Size: ");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut settings.width).range(100..=4000));
+                        ui.label("×");
+                        ui.add(egui::DragValue::new(&mut settings.height).range(100..=4000));
+                        ui.label("PNG scale: ");
+                        ui.add(
+                            egui::DragValue::new(&mut settings.scale)
+                                .speed(0.1)
+                                .range(0.5..=8.0),
+                        );
+                    });
+                    ui.end_row();
+                    for (label, value, default) in [
+                        ("Title: ", &mut settings.title, &figure.title),
+                        ("X axis: ", &mut settings.x_label, &figure.x_label),
+                        ("Y axis: ", &mut settings.y_label, &figure.y_label),
+                    ] {
+                        ui.label(label);
+                        ui.add(egui::TextEdit::singleline(value).hint_text(default.as_str()));
+                        ui.end_row();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let name = file_stem(settings.title_or(&figure.title));
+                    if ui.button("PNG").clicked() {
+                        match figure.to_png(&settings) {
+                            Ok(png) => export = Some((format!("{name}.png"), png)),
+                            Err(e) => tracing::error!("Figure::to_png: {e:?}"),
+                        }
+                    }
+                    if ui.button("SVG").clicked() {
+                        export =
+                            Some((format!("{name}.svg"), figure.to_svg(&settings).into_bytes()));
+                    }
+                });
+            });
+        ui.data_mut(|d| d.insert_persisted(id, settings));
+        export
+    }
+}
+
+/// Round tick positions covering `min` to `max`, about six of them
+fn ticks(min: f64, max: f64) -> Vec<f64> {
+    let raw = (max - min) / 6.0;
+    if !raw.is_finite() || raw <= 0.0 {
+        return Vec::new();
+    }
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|step| *step >= raw)
+        .unwrap_or(10.0 * magnitude);
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+fn format_tick(value: f64) -> String {
+    let value = if value.abs() < 1e-12 { 0.0 } else { value };
+    let text = format!("{value:.6}");
+    text.trim_end_matches('0').trim_end_matches('.').to_owned()
+}
+
+fn svg_color(color: egui::Color32) -> String {
+    let [r, g, b, _] = color.to_srgba_unmultiplied();
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// File name for an exported figure titled `title`
+fn file_stem(title: &str) -> String {
+    let stem = title
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    let stem = stem.trim_matches('_');
+    if stem.is_empty() {
+        "plot".to_owned()
+    } else {
+        stem.to_owned()
+    }
+}
@@ -1,10 +1,27 @@
+use crate::{
+    ResolutionCheck, VarianceComponents,
+    varcomp_table::{source_label, summary_height, summary_ui},
+};
 use eframe::egui;
-use gage_study::study_evaluation::StudyEvaluation;
+
+/// Gage resolution with a warning for each way it is too coarse
+fn resolution_ui(ui: &mut egui::Ui, check: &ResolutionCheck, warnings: &[String]) {
+    ui.label(format!(
+        "Resolution: {}{}   Distinct values per part (min): {}   Range categories: {}",
+        check.resolution,
+        if check.inferred { " (inferred)" } else { "" },
+        check.min_distinct().unwrap_or(0),
+        check.range_categories
+    ));
+    for warning in warnings.iter() {
+        ui.colored_label(egui::Color32::ORANGE, format!("⚠ {warning}"));
+    }
+}
 /// Shows off a table with dynamic layout
 pub struct GageEvalTableView<'a> {
     pub striped: bool,
     pub resizable: bool,
-    pub dataset: &'a Option<StudyEvaluation>,
+    pub dataset: &'a Option<VarianceComponents>,
 }
 
 impl Default for GageEvalTableView<'_> {
@@ -25,7 +42,7 @@ impl<'a> GageEvalTableView<'a> {
     pub fn show(
         &mut self,
         ctx: &egui::Context,
-        dataset: &'a Option<StudyEvaluation>,
+        dataset: &'a Option<VarianceComponents>,
         open: &mut bool,
     ) {
         self.dataset = dataset;
@@ -41,14 +58,30 @@ impl<'a> GageEvalTableView<'a> {
     pub fn ui(&mut self, ui: &mut egui::Ui) {
         ui.style_mut().override_text_style = Some(egui::style::TextStyle::Monospace);
         use egui_extras::{Size, StripBuilder};
+        let warnings = self
+            .dataset
+            .as_ref()
+            .map_or(Vec::new(), |study| study.resolution_warnings());
         StripBuilder::new(ui)
             .size(Size::remainder().at_least(100.0)) // for the table
+            .size(Size::exact(summary_height(self.dataset))) // for the estimation method and adjustments
+            .size(Size::exact(20.0 + 18.0 * warnings.len() as f32)) // for the gage resolution
             .vertical(|mut strip| {
                 strip.cell(|ui| {
                     egui::ScrollArea::horizontal().show(ui, |ui| {
                         self.table_ui(ui);
                     });
                 });
+                strip.cell(|ui| {
+                    if let Some(study) = self.dataset {
+                        summary_ui(ui, study);
+                    }
+                });
+                strip.cell(|ui| {
+                    if let Some(check) = self.dataset.as_ref().and_then(|s| s.resolution.as_ref()) {
+                        resolution_ui(ui, check, &warnings);
+                    }
+                });
             });
     }
 
@@ -89,6 +122,20 @@ impl<'a> GageEvalTableView<'a> {
                     .clip(true),
             )
             .min_scrolled_height(0.0);
+        let historical = self
+            .dataset
+            .as_ref()
+            .is_some_and(|v| v.historical_stddev.is_some());
+        let table = if historical {
+            table.column(
+                Column::initial(100.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+            )
+        } else {
+            table
+        };
 
         let proc_var = self.dataset.as_ref().map_or(5.15, |v| v.process_variation);
 
@@ -124,6 +171,14 @@ impl<'a> GageEvalTableView<'a> {
                         ui.strong("(SV/Tol)");
                     });
                 });
+                if historical {
+                    header.col(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.strong("%Process");
+                            ui.strong("(6SD/6σhist)");
+                        });
+                    });
+                }
             })
             .body(|mut body| {
                 if let Some(study) = self.dataset {
@@ -155,10 +210,19 @@ impl<'a> GageEvalTableView<'a> {
                                     * 100.0
                             ));
                         });
+                        if let Some(percent) = study.percent_process(study.total_gagerr.stddev) {
+                            row.col(|ui| {
+                                ui.label(format!("{:>10.2}", percent));
+                            });
+                        }
                     });
                     body.row(row_height, |mut row| {
                         row.col(|ui| {
-                            ui.label("Repeatability");
+                            source_label(
+                                ui,
+                                "Repeatability",
+                                study.total_gagerr.repeatability.adjusted,
+                            );
                         });
                         row.col(|ui| {
                             ui.label(format!("{:>10.6}", study.total_gagerr.repeatability.stddev));
@@ -185,11 +249,22 @@ impl<'a> GageEvalTableView<'a> {
                                     * 100.0
                             ));
                         });
+                        if let Some(percent) =
+                            study.percent_process(study.total_gagerr.repeatability.stddev)
+                        {
+                            row.col(|ui| {
+                                ui.label(format!("{:>10.2}", percent));
+                            });
+                        }
                     });
-                    if study.use_interaction {
+                    if study.show_reproducibility {
                         body.row(row_height, |mut row| {
                             row.col(|ui| {
-                                ui.label("Reproducibility");
+                                source_label(
+                                    ui,
+                                    "Reproducibility",
+                                    study.total_gagerr.reproducibility.adjusted,
+                                );
                             });
                             row.col(|ui| {
                                 ui.label(format!(
@@ -221,11 +296,18 @@ impl<'a> GageEvalTableView<'a> {
                                         * 100.0
                                 ));
                             });
+                            if let Some(percent) =
+                                study.percent_process(study.total_gagerr.reproducibility.stddev)
+                            {
+                                row.col(|ui| {
+                                    ui.label(format!("{:>10.2}", percent));
+                                });
+                            }
                         });
                     }
                     body.row(row_height, |mut row| {
                         row.col(|ui| {
-                            ui.label("Part-to-Part");
+                            source_label(ui, "Part-to-Part", study.part_to_part.adjusted);
                         });
                         row.col(|ui| {
                             ui.label(format!("{:>10.6}", study.part_to_part.stddev));
@@ -250,6 +332,11 @@ impl<'a> GageEvalTableView<'a> {
                                     * 100.0
                             ));
                         });
+                        if let Some(percent) = study.percent_process(study.part_to_part.stddev) {
+                            row.col(|ui| {
+                                ui.label(format!("{:>10.2}", percent));
+                            });
+                        }
                     });
                     body.row(row_height, |mut row| {
                         row.col(|ui| {
@@ -275,6 +362,11 @@ impl<'a> GageEvalTableView<'a> {
                                     * 100.0
                             ));
                         });
+                        if let Some(percent) = study.percent_process(study.total_variation.stddev) {
+                            row.col(|ui| {
+                                ui.label(format!("{:>10.2}", percent));
+                            });
+                        }
                     });
                 }
             });
@@ -0,0 +1,380 @@
+use anyhow::{Result, bail};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+    mpsc,
+};
+use std::time::Duration;
+
+/// Output format of the connected gauge
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum GaugeProtocol {
+    /// Mitutoyo Digimatic as sent by U-WAVE and USB-ITN interfaces, e.g. `01A+00012.345`
+    Mitutoyo,
+    /// Sylvac RS-232/USB output, e.g. `+0012.345` or `12.345 mm`
+    Sylvac,
+    /// First number on each line
+    Plain,
+}
+
+impl GaugeProtocol {
+    pub const ALL: [GaugeProtocol; 3] = [
+        GaugeProtocol::Mitutoyo,
+        GaugeProtocol::Sylvac,
+        GaugeProtocol::Plain,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GaugeProtocol::Mitutoyo => "Mitutoyo Digimatic / U-WAVE",
+            GaugeProtocol::Sylvac => "Sylvac",
+            GaugeProtocol::Plain => "Plain number",
+        }
+    }
+
+    /// Parses one line received from the gauge
+    pub fn parse(&self, line: &str) -> Result<f64> {
+        let line = line.trim();
+        let value = match self {
+            GaugeProtocol::Mitutoyo => {
+                // Two digit channel followed by a status letter, `A` for a valid reading
+                let bytes = line.as_bytes();
+                if bytes.len() < 4
+                    || !bytes[0].is_ascii_digit()
+                    || !bytes[1].is_ascii_digit()
+                    || !bytes[2].is_ascii_alphabetic()
+                {
+                    bail!("\"{line}\" is not a Digimatic reading");
+                }
+                if bytes[2] != b'A' {
+                    bail!("Gauge reported status \"{}\"", &line[..3]);
+                }
+                without_units(&line[3..]).parse::<f64>().ok()
+            }
+            GaugeProtocol::Sylvac => without_units(line).parse::<f64>().ok(),
+            GaugeProtocol::Plain => line
+                .split(|c: char| c.is_whitespace() || c == ';' || c == ',')
+                .find_map(|token| without_units(token).parse::<f64>().ok()),
+        };
+        match value {
+            Some(value) if value.is_finite() => Ok(value),
+            _ => bail!("\"{line}\" is not a {} reading", self.label()),
+        }
+    }
+}
+
+/// `text` without a trailing unit such as `mm`, `in` or `"`
+fn without_units(text: &str) -> &str {
+    text.trim()
+        .trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '"')
+        .trim()
+}
+
+enum GaugeEvent {
+    Line(String),
+    Error(String),
+}
+
+struct GaugeConnection {
+    port: String,
+    receiver: mpsc::Receiver<GaugeEvent>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for GaugeConnection {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Live gauge input from a serial port or pseudo-terminal (native only). Readings
+/// are returned by `poll` to be fed into guided data entry. Keyboard-wedge gauges
+/// need no connection: they type into the data entry box directly.
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct GaugeInput {
+    /// Device path, e.g. `/dev/ttyUSB0`, `COM3` or a `socat` pty such as `/dev/pts/4`
+    pub port: String,
+    pub baud_rate: u32,
+    pub protocol: GaugeProtocol,
+    #[serde(skip)]
+    connection: Option<GaugeConnection>,
+    #[serde(skip)]
+    last_line: Option<String>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl Default for GaugeInput {
+    fn default() -> Self {
+        Self {
+            port: String::new(),
+            baud_rate: 9600,
+            protocol: GaugeProtocol::Mitutoyo,
+            connection: None,
+            last_line: None,
+            error: None,
+        }
+    }
+}
+
+impl GaugeInput {
+    pub fn name(&self) -> &'static str {
+        "☰ Gauge Input"
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some()
+    }
+
+    /// Opens the port and starts reading lines on a background thread. `ctx` is
+    /// repainted whenever a line arrives so readings appear without user input.
+    pub fn connect(&mut self, ctx: &egui::Context) -> Result<()> {
+        self.disconnect();
+        let port = self.port.trim().to_owned();
+        if port.is_empty() {
+            bail!("No serial port selected");
+        }
+        let mut serial = serialport::new(port.as_str(), self.baud_rate)
+            .timeout(Duration::from_millis(100))
+            .open()?;
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let mut line = Vec::new();
+            let mut buffer = [0u8; 256];
+            while !thread_stop.load(Ordering::Relaxed) {
+                match serial.read(&mut buffer) {
+                    Ok(n) => {
+                        for &byte in buffer[..n].iter() {
+                            if byte != b'\r' && byte != b'\n' {
+                                line.push(byte);
+                            } else if !line.is_empty() {
+                                let text = String::from_utf8_lossy(&line).into_owned();
+                                line.clear();
+                                if sender.send(GaugeEvent::Line(text)).is_err() {
+                                    return;
+                                }
+                                ctx.request_repaint();
+                            }
+                        }
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+                    Err(e) => {
+                        let _ = sender.send(GaugeEvent::Error(e.to_string()));
+                        ctx.request_repaint();
+                        return;
+                    }
+                }
+            }
+        });
+        self.connection = Some(GaugeConnection {
+            port,
+            receiver,
+            stop,
+        });
+        Ok(())
+    }
+
+    pub fn disconnect(&mut self) {
+        self.connection = None;
+    }
+
+    /// Readings received since the last call; lines that fail to parse are
+    /// reported in the window and skipped
+    pub fn poll(&mut self) -> Vec<f64> {
+        let Some(connection) = &self.connection else {
+            return Vec::new();
+        };
+        let mut readings = Vec::new();
+        let mut lost = false;
+        while let Ok(event) = connection.receiver.try_recv() {
+            match event {
+                GaugeEvent::Line(line) => {
+                    match self.protocol.parse(&line) {
+                        Ok(value) => {
+                            readings.push(value);
+                            self.error = None;
+                        }
+                        Err(e) => {
+                            tracing::error!("GaugeProtocol::parse: {e:?}");
+                            self.error = Some(e.to_string());
+                        }
+                    }
+                    self.last_line = Some(line);
+                }
+                GaugeEvent::Error(e) => {
+                    tracing::error!("GaugeInput: {e}");
+                    self.error = Some(format!("{}: {e}", connection.port));
+                    lost = true;
+                }
+            }
+        }
+        if lost {
+            self.disconnect();
+        }
+        readings
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                self.ui(ui);
+            });
+    }
+
+    /// Shows `error` in the window until the next reading arrives
+    pub fn report_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let connected = self.is_connected();
+        ui.add_enabled_ui(!connected, |ui| {
+            egui::Grid::new("gauge_settings").show(ui, |ui| {
+                ui.label("Port: ");
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.port).hint_text("/dev/ttyUSB0"));
+                    egui::ComboBox::from_id_salt("gauge_ports")
+                        .selected_text("Detected")
+                        .show_ui(ui, |ui| {
+                            for port in serialport::available_ports().unwrap_or_default() {
+                                ui.selectable_value(
+                                    &mut self.port,
+                                    port.port_name.clone(),
+                                    port.port_name,
+                                );
+                            }
+                        });
+                });
+                ui.end_row();
+                ui.label("Baud rate: ");
+                egui::ComboBox::from_id_salt("gauge_baud_rate")
+                    .selected_text(self.baud_rate.to_string())
+                    .show_ui(ui, |ui| {
+                        for baud_rate in [2400, 4800, 9600, 19200, 38400, 57600, 115200] {
+                            ui.selectable_value(
+                                &mut self.baud_rate,
+                                baud_rate,
+                                baud_rate.to_string(),
+                            );
+                        }
+                    });
+                ui.end_row();
+                ui.label("Protocol: ");
+                egui::ComboBox::from_id_salt("gauge_protocol")
+                    .selected_text(self.protocol.label())
+                    .show_ui(ui, |ui| {
+                        for protocol in GaugeProtocol::ALL {
+                            ui.selectable_value(&mut self.protocol, protocol, protocol.label());
+                        }
+                    });
+                ui.end_row();
+            });
+        });
+        ui.horizontal(|ui| {
+            if connected {
+                if ui.button("Disconnect").clicked() {
+                    self.disconnect();
+                }
+            } else if ui.button("Connect").clicked() {
+                match self.connect(ui.ctx()) {
+                    Ok(()) => self.error = None,
+                    Err(e) => {
+                        tracing::error!("GaugeInput::connect: {e:?}");
+                        self.error = Some(e.to_string());
+                    }
+                }
+            }
+            match &self.connection {
+                Some(connection) => {
+                    ui.colored_label(egui::Color32::GREEN, format!("● {}", connection.port))
+                }
+                None => ui.label("○ Not connected"),
+            };
+        });
+        if let Some(line) = &self.last_line {
+            ui.label(format!("Last received: {line}"));
+        }
+        if let Some(err) = &self.error {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+        ui.separator();
+        ui.label(
+            "Readings go to the current run in Guided Data Entry. Keyboard-wedge gauges \
+             need no connection: focus the entry box and trigger the gauge.",
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mitutoyo_readings() {
+        let parse = |line| GaugeProtocol::Mitutoyo.parse(line).ok();
+        assert_eq!(parse("01A+00012.345"), Some(12.345));
+        assert_eq!(parse("01A-00001.250\r\n"), Some(-1.25));
+        assert_eq!(parse("  02A+00000.500 mm\r\n"), Some(0.5));
+        assert_eq!(parse("01A+0000.1234in"), Some(0.1234));
+    }
+
+    #[test]
+    fn mitutoyo_rejects_status_and_garbage() {
+        let parse = |line| GaugeProtocol::Mitutoyo.parse(line);
+        assert!(parse("01B+00012.345").is_err());
+        assert!(parse("01A").is_err());
+        assert!(parse("01A+").is_err());
+        assert!(parse("+00012.345").is_err());
+        assert!(parse("\u{0}\u{ff}garbage").is_err());
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn sylvac_readings() {
+        let parse = |line| GaugeProtocol::Sylvac.parse(line).ok();
+        assert_eq!(parse("+0012.345"), Some(12.345));
+        assert_eq!(parse("-0000.015\r\n"), Some(-0.015));
+        assert_eq!(parse("12.345 mm"), Some(12.345));
+        assert_eq!(parse(" 0.4860\"\r"), Some(0.486));
+        assert_eq!(parse("-1.5in"), Some(-1.5));
+    }
+
+    #[test]
+    fn sylvac_rejects_garbage() {
+        let parse = |line| GaugeProtocol::Sylvac.parse(line);
+        assert!(parse("mm").is_err());
+        assert!(parse("ERR 12").is_err());
+        assert!(parse("12.3.4").is_err());
+        assert!(parse("NaN").is_err());
+        assert!(parse("\r\n").is_err());
+    }
+
+    #[test]
+    fn plain_readings() {
+        let parse = |line| GaugeProtocol::Plain.parse(line).ok();
+        assert_eq!(parse("12.5"), Some(12.5));
+        assert_eq!(parse("  -3.25\r\n"), Some(-3.25));
+        assert_eq!(parse("+7"), Some(7.0));
+        assert_eq!(parse("Value: 12.5 mm"), Some(12.5));
+        assert_eq!(parse("12.5mm;ok"), Some(12.5));
+    }
+
+    #[test]
+    fn plain_rejects_garbage() {
+        let parse = |line| GaugeProtocol::Plain.parse(line);
+        assert!(parse("no reading").is_err());
+        assert!(parse("inf").is_err());
+        assert!(parse("").is_err());
+        assert!(parse("\u{1b}[2J").is_err());
+    }
+}
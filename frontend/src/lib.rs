@@ -2,19 +2,141 @@
 
 mod anova_table;
 mod app;
+mod attribute_agreement;
+mod attribute_agreement_table;
+mod attribute_data;
+mod attribute_data_table;
+mod data_entry;
 mod data_table;
+mod expanded_anova;
+mod expanded_anova_table;
+mod expanded_data;
+mod figure;
 mod gage_eval_table;
+#[cfg(not(target_arch = "wasm32"))]
+mod gauge_input;
+mod linearity_plot;
+mod linearity_study;
+mod linearity_table;
+mod nested_anova;
+mod nested_anova_table;
+mod operator_chart;
+mod outliers;
+mod reml;
+mod residual_plots;
+mod residuals;
+mod resolution;
+mod simulation;
+mod simulator;
+mod spec_limits;
+mod stability_chart;
+mod stability_data;
+mod stability_study;
+mod stability_table;
 mod statistics;
+mod study_plan;
+mod study_planner;
 mod study_plots;
+mod type1_run_chart;
+mod type1_study;
+mod type1_table;
 mod varcomp_table;
+mod variance_components;
+mod worksheet;
+mod worksheet_generator;
 
 pub use anova_table::AnovaTableView;
 pub use app::GageStudyApp;
+pub use attribute_agreement::AttributeAgreement;
+pub use attribute_agreement_table::AttributeAgreementTableView;
+pub use attribute_data::AttributeData;
+pub use attribute_data_table::AttributeDataTableView;
+pub use data_entry::{DataEntry, DataEntryEvent};
 pub use data_table::DataTableView;
+pub use expanded_anova::{ExpandedAnova, FactorSpec};
+pub use expanded_anova_table::ExpandedAnovaTableView;
+pub use expanded_data::ExpandedData;
+pub use figure::{BarSummary, BoxSummary, ExportSettings, Figure, Mark, Marker, Series};
 pub use gage_eval_table::GageEvalTableView;
+#[cfg(not(target_arch = "wasm32"))]
+pub use gauge_input::{GaugeInput, GaugeProtocol};
+pub use linearity_plot::LinearityPlot;
+pub use linearity_study::LinearityStudy;
+pub use linearity_table::LinearityTableView;
+pub use nested_anova::NestedAnova;
+pub use nested_anova_table::NestedAnovaTableView;
+pub use operator_chart::{OperatorChart, Subgroup};
+pub use outliers::{Outlier, OutlierReason, OutlierScreen};
+pub use reml::Reml;
+pub use residual_plots::ResidualPlots;
+pub use residuals::Residuals;
+pub use resolution::ResolutionCheck;
+pub use simulation::{Sigmas, Simulation};
+pub use simulator::Simulator;
+pub use spec_limits::SpecLimits;
+pub use stability_chart::StabilityChart;
+pub use stability_data::StabilityData;
+pub use stability_study::{ChartType, ControlChart, RuleSet, StabilityStudy};
+pub use stability_table::StabilityTableView;
 pub use statistics::Statistics;
-pub use study_plots::{PlotType, StudyPlots};
+pub use study_plan::{Design, StudyPlan};
+pub use study_planner::StudyPlanner;
+pub use study_plots::{PlotOutput, PlotType, StudyPlots};
+pub use type1_run_chart::Type1RunChart;
+pub use type1_study::Type1Study;
+pub use type1_table::Type1TableView;
 pub use varcomp_table::VarCompTableView;
+pub use variance_components::{EstimationMethod, VarianceComponents};
+pub use worksheet::{Worksheet, WorksheetRow};
+pub use worksheet_generator::WorksheetGenerator;
+
+/// Builds a `Data` record through serde, so the record layout stays owned by `gage_study`
+pub(crate) fn make_data(
+    name: &str,
+    part: &str,
+    operator: &str,
+    replicate: usize,
+    measured: f64,
+    nominal: f64,
+) -> anyhow::Result<gage_study::data::Data> {
+    Ok(serde_json::from_value(serde_json::json!({
+        "name": name,
+        "part": part,
+        "operator": operator,
+        "replicate": replicate,
+        "measured": measured,
+        "nominal": nominal,
+    }))?)
+}
+
+/// Escapes text for SVG and HTML exports, inside elements or quoted attributes
+pub(crate) fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Two parts crossed with two operators, two replicates per cell, shared by the
+/// ANOVA and REML tests. Crossed mean squares are 72 (parts), 18 (operators),
+/// 8 (interaction) and 2 (repeatability).
+#[cfg(test)]
+pub(crate) fn two_by_two_data() -> Vec<gage_study::data::Data> {
+    [
+        ("1", "A", [1.0, 3.0]),
+        ("1", "B", [2.0, 4.0]),
+        ("2", "A", [5.0, 7.0]),
+        ("2", "B", [10.0, 12.0]),
+    ]
+    .iter()
+    .flat_map(|(part, operator, values)| {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| make_data("", part, operator, i + 1, *v, 0.0).unwrap())
+    })
+    .collect()
+}
 
 static DEMO_DATA_A: &str = include_str!("../operatorA.json");
 static DEMO_DATA_B: &str = include_str!("../operatorB.json");
@@ -34,3 +156,15 @@ example A10,part 1,operator A,1,2.78,3.0,
 example A11,part 1,operator A,1,2.98,3.0,
 example A12,part 1,operator A,1,2.99,3.0,
 "#;
+static EXAMPLE_ATTRIBUTE_CSV: &str = r#"
+name,part,operator,replicate,rating,standard,
+example A11,part 1,operator A,1,pass,pass,
+example A12,part 1,operator A,2,fail,pass,
+example A21,part 2,operator A,1,fail,fail,
+"#;
+static EXAMPLE_STABILITY_CSV: &str = r#"
+name,part,operator,replicate,measured,nominal,timestamp,
+master 1,master,operator A,1,3.01,3.0,2024-03-01T08:00,
+master 2,master,operator A,1,2.99,3.0,2024-03-02T08:00,
+master 3,master,operator A,1,3.02,3.0,2024-03-03T08:00,
+"#;
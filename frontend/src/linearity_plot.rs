@@ -0,0 +1,117 @@
+use crate::{ExportSettings, Figure, LinearityStudy, Mark, Marker};
+use eframe::egui;
+
+/// Number of segments used to draw the fitted line and its confidence bands
+const BAND_SEGMENTS: usize = 50;
+
+#[derive(Default)]
+pub struct LinearityPlot<'a> {
+    pub study: Option<&'a LinearityStudy>,
+}
+
+impl<'a> LinearityPlot<'a> {
+    pub fn name(&self) -> &'static str {
+        "☰ Linearity and Bias Plot"
+    }
+
+    /// Shows the bias plot, returning the file name and content of an exported image
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        study: &'a Option<LinearityStudy>,
+        open: &mut bool,
+    ) -> Option<(String, Vec<u8>)> {
+        self.study = study.as_ref();
+        let mut export = None;
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                export = self.ui(ui);
+            });
+        export
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<(String, Vec<u8>)> {
+        let figure = Self::bias_plot(self.study?);
+        let export = ExportSettings::ui(ui, "linearity_bias", &figure);
+        figure.show(ui, "linearity_bias", &[], &[]);
+        export
+    }
+
+    fn bias_plot(study: &LinearityStudy) -> Figure {
+        // Fitted line and 95% confidence bands across the reference range
+        let x_min = study.references.first().map_or(0.0, |r| r.reference);
+        let x_max = study.references.last().map_or(0.0, |r| r.reference);
+        let xs = (0..=BAND_SEGMENTS)
+            .map(|i| x_min + (x_max - x_min) * i as f64 / BAND_SEGMENTS as f64)
+            .collect::<Vec<f64>>();
+        let bands = xs
+            .iter()
+            .map(|x| (*x, study.confidence_band(*x, 0.05)))
+            .collect::<Vec<(f64, (f64, f64))>>();
+        let mut figure = Figure::new("Linearity and Bias", "Reference Value", "Bias");
+        figure.push(
+            "zero bias",
+            egui::Color32::DARK_GRAY,
+            Mark::HLine {
+                y: 0.0,
+                width: 1.0,
+                dashed: true,
+            },
+        );
+        for band in [
+            bands.iter().map(|(x, b)| [*x, b.0]).collect(),
+            bands.iter().map(|(x, b)| [*x, b.1]).collect(),
+        ] {
+            figure.push(
+                "95% CI",
+                egui::Color32::LIGHT_BLUE,
+                Mark::Line {
+                    points: band,
+                    highlight: false,
+                    dashed: true,
+                },
+            );
+        }
+        figure.push(
+            "regression",
+            egui::Color32::BLUE,
+            Mark::Line {
+                points: xs.iter().map(|x| [*x, study.fitted(*x)]).collect(),
+                highlight: false,
+                dashed: false,
+            },
+        );
+        figure.push(
+            "bias",
+            egui::Color32::GRAY,
+            Mark::Points {
+                points: study.points.clone(),
+                marker: Marker::Circle,
+                radius: 3.0,
+                filled: false,
+                highlight: false,
+                rows: Vec::new(),
+            },
+        );
+        figure.push(
+            "average bias",
+            egui::Color32::RED,
+            Mark::Points {
+                points: study
+                    .references
+                    .iter()
+                    .map(|r| [r.reference, r.bias])
+                    .collect(),
+                marker: Marker::Square,
+                radius: 4.0,
+                filled: true,
+                highlight: false,
+                rows: Vec::new(),
+            },
+        );
+        figure
+    }
+}
@@ -0,0 +1,257 @@
+use crate::statistics::{t_critical, t_test_p_value};
+use anyhow::{Result, bail};
+use gage_study::data::Data;
+use std::collections::BTreeMap;
+
+/// Bias of the repeated readings of a single reference part
+pub struct ReferenceBias {
+    pub part: String,
+    pub reference: f64,
+    pub count: usize,
+    pub mean: f64,
+    pub stddev: f64,
+    pub bias: f64,
+    pub p_value: f64,
+}
+
+/// AIAG gage linearity and bias study. Reference values are taken from
+/// `Data::nominal` and readings are grouped by part.
+pub struct LinearityStudy {
+    pub references: Vec<ReferenceBias>,
+    /// (reference, bias) for every individual reading
+    pub points: Vec<[f64; 2]>,
+    pub slope: f64,
+    pub intercept: f64,
+    pub slope_se: f64,
+    pub intercept_se: f64,
+    pub slope_p: f64,
+    pub intercept_p: f64,
+    pub r_squared: f64,
+    pub s: f64,
+    pub average_bias: f64,
+    pub average_bias_p: f64,
+    pub process_variation: f64,
+    pub linearity: f64,
+    pub percent_linearity: f64,
+    pub percent_bias: f64,
+    x_mean: f64,
+    sxx: f64,
+}
+
+impl LinearityStudy {
+    pub fn from_data(data: &[Data]) -> Result<Self> {
+        let mut groups: BTreeMap<&str, Vec<&Data>> = BTreeMap::new();
+        for d in data.iter() {
+            groups.entry(d.part.as_str()).or_default().push(d);
+        }
+        let mut references = Vec::new();
+        for (part, readings) in groups.into_iter() {
+            let reference = readings[0].nominal;
+            if readings.iter().any(|d| d.nominal != reference) {
+                bail!("Part {part} has more than one reference (nominal) value");
+            }
+            if readings.len() < 2 {
+                bail!("Part {part} requires at least two readings");
+            }
+            let count = readings.len();
+            let n = count as f64;
+            let mean = readings.iter().map(|d| d.measured).sum::<f64>() / n;
+            let stddev = (readings
+                .iter()
+                .map(|d| (d.measured - mean).powi(2))
+                .sum::<f64>()
+                / (n - 1.0))
+                .sqrt();
+            let bias = mean - reference;
+            references.push(ReferenceBias {
+                part: part.to_owned(),
+                reference,
+                count,
+                mean,
+                stddev,
+                bias,
+                p_value: t_test_p_value(bias / (stddev / n.sqrt()), n - 1.0),
+            });
+        }
+        references.sort_by(|a, b| a.reference.total_cmp(&b.reference));
+        if references.len() < 2 {
+            bail!("Linearity study requires at least two reference parts");
+        }
+
+        // Least squares regression of individual bias on reference value
+        let points = data
+            .iter()
+            .map(|d| [d.nominal, d.measured - d.nominal])
+            .collect::<Vec<[f64; 2]>>();
+        let n = points.len() as f64;
+        let x_mean = points.iter().map(|p| p[0]).sum::<f64>() / n;
+        let y_mean = points.iter().map(|p| p[1]).sum::<f64>() / n;
+        let sxx = points.iter().map(|p| (p[0] - x_mean).powi(2)).sum::<f64>();
+        let sxy = points
+            .iter()
+            .map(|p| (p[0] - x_mean) * (p[1] - y_mean))
+            .sum::<f64>();
+        let syy = points.iter().map(|p| (p[1] - y_mean).powi(2)).sum::<f64>();
+        if sxx == 0.0 {
+            bail!("Linearity study requires distinct reference values");
+        }
+        let slope = sxy / sxx;
+        let intercept = y_mean - slope * x_mean;
+        let sse = points
+            .iter()
+            .map(|p| (p[1] - intercept - slope * p[0]).powi(2))
+            .sum::<f64>();
+        let dof = n - 2.0;
+        let s = (sse / dof).sqrt();
+        let slope_se = s / sxx.sqrt();
+        let intercept_se = s * (1.0 / n + x_mean.powi(2) / sxx).sqrt();
+        let r_squared = if syy > 0.0 { 1.0 - sse / syy } else { 0.0 };
+
+        // Average bias tested against the pooled within-reference deviation
+        let pooled_dof = references.iter().map(|r| r.count - 1).sum::<usize>() as f64;
+        let pooled_sd = (references
+            .iter()
+            .map(|r| (r.count - 1) as f64 * r.stddev.powi(2))
+            .sum::<f64>()
+            / pooled_dof)
+            .sqrt();
+
+        let mut study = Self {
+            references,
+            points,
+            slope,
+            intercept,
+            slope_se,
+            intercept_se,
+            slope_p: t_test_p_value(slope / slope_se, dof),
+            intercept_p: t_test_p_value(intercept / intercept_se, dof),
+            r_squared,
+            s,
+            average_bias: y_mean,
+            average_bias_p: t_test_p_value(y_mean / (pooled_sd / n.sqrt()), pooled_dof),
+            process_variation: 1.0,
+            linearity: 0.0,
+            percent_linearity: 0.0,
+            percent_bias: 0.0,
+            x_mean,
+            sxx,
+        };
+        study.evaluate();
+        Ok(study)
+    }
+
+    pub fn with_process_variation(mut self, process_variation: f64) -> Self {
+        self.process_variation = process_variation;
+        self.evaluate();
+        self
+    }
+
+    /// Fitted bias at reference value `x`
+    pub fn fitted(&self, x: f64) -> f64 {
+        self.intercept + self.slope * x
+    }
+
+    /// Confidence interval on the fitted bias at reference value `x`
+    pub fn confidence_band(&self, x: f64, alpha: f64) -> (f64, f64) {
+        let n = self.points.len() as f64;
+        let half_width = t_critical(alpha, n - 2.0)
+            * self.s
+            * (1.0 / n + (x - self.x_mean).powi(2) / self.sxx).sqrt();
+        let fit = self.fitted(x);
+        (fit - half_width, fit + half_width)
+    }
+
+    fn evaluate(&mut self) {
+        self.linearity = self.slope.abs() * self.process_variation;
+        self.percent_linearity = self.slope.abs() * 100.0;
+        self.percent_bias = self.average_bias.abs() / self.process_variation * 100.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::make_data;
+
+    /// AIAG MSA reference manual linearity example: five reference parts
+    /// measured twelve times each
+    fn data() -> Vec<Data> {
+        [
+            (
+                2.0,
+                [2.7, 2.5, 2.4, 2.5, 2.7, 2.3, 2.5, 2.5, 2.4, 2.4, 2.6, 2.4],
+            ),
+            (
+                4.0,
+                [5.1, 3.9, 4.2, 5.0, 3.8, 3.9, 3.9, 3.9, 3.9, 4.0, 4.1, 3.8],
+            ),
+            (
+                6.0,
+                [5.8, 5.7, 5.9, 5.9, 6.0, 6.1, 6.0, 6.1, 6.4, 6.3, 6.0, 6.1],
+            ),
+            (
+                8.0,
+                [7.6, 7.7, 7.8, 7.7, 7.8, 7.8, 7.8, 7.7, 7.8, 7.5, 7.6, 7.7],
+            ),
+            (
+                10.0,
+                [9.1, 9.3, 9.5, 9.3, 9.4, 9.5, 9.5, 9.5, 9.6, 9.2, 9.3, 9.4],
+            ),
+        ]
+        .iter()
+        .flat_map(|(reference, values)| {
+            values.iter().enumerate().map(|(i, v)| {
+                make_data("", &reference.to_string(), "A", i + 1, *v, *reference).unwrap()
+            })
+        })
+        .collect()
+    }
+
+    #[test]
+    fn reference_biases_are_sorted_by_reference() {
+        let study = LinearityStudy::from_data(&data()).unwrap();
+        // Part "10" sorts before "2" by name but last by reference
+        let references = study
+            .references
+            .iter()
+            .map(|r| r.reference)
+            .collect::<Vec<f64>>();
+        assert_eq!(references, vec![2.0, 4.0, 6.0, 8.0, 10.0]);
+        let biases = [0.491667, 0.125, 0.025, -0.291667, -0.616667];
+        for (r, bias) in study.references.iter().zip(biases) {
+            assert_eq!(r.count, 12);
+            assert!((r.bias - bias).abs() < 1e-6);
+        }
+        assert!((study.average_bias + 0.053333).abs() < 1e-6);
+    }
+
+    #[test]
+    fn regression_matches_aiag_example() {
+        let study = LinearityStudy::from_data(&data()).unwrap();
+        assert!((study.slope + 0.131667).abs() < 1e-6);
+        assert!((study.intercept - 0.736667).abs() < 1e-6);
+        assert!((study.r_squared - 0.714318).abs() < 1e-6);
+        assert!((study.s - 0.239540).abs() < 1e-6);
+        // Both the slope and the intercept differ significantly from zero
+        assert!((study.slope / study.slope_se + 12.0426).abs() < 1e-4);
+        assert!((study.intercept / study.intercept_se - 10.1575).abs() < 1e-4);
+        assert!(study.slope_p < 1e-10);
+        assert!(study.intercept_p < 1e-10);
+        assert!((study.percent_linearity - 13.1667).abs() < 1e-4);
+    }
+
+    #[test]
+    fn confidence_band_is_narrowest_at_the_mean_reference() {
+        let study = LinearityStudy::from_data(&data()).unwrap();
+        // 95% band with t(0.975, 58) = 2.00172
+        let expected = [
+            (2.0, 0.366116, 0.580551),
+            (6.0, -0.115235, 0.008569),
+            (10.0, -0.687217, -0.472783),
+        ];
+        for (x, lower, upper) in expected {
+            let (l, u) = study.confidence_band(x, 0.05);
+            assert!((l - lower).abs() < 1e-5 && (u - upper).abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use crate::LinearityStudy;
+use eframe::egui;
+/// Per-reference bias and the bias regression of a gage linearity study
+pub struct LinearityTableView<'a> {
+    pub striped: bool,
+    pub resizable: bool,
+    pub dataset: &'a Option<LinearityStudy>,
+}
+
+impl Default for LinearityTableView<'_> {
+    fn default() -> Self {
+        Self {
+            striped: true,
+            resizable: true,
+            dataset: &None,
+        }
+    }
+}
+
+impl<'a> LinearityTableView<'a> {
+    pub fn name(&self) -> &'static str {
+        "☰ Linearity and Bias Table"
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        dataset: &'a Option<LinearityStudy>,
+        open: &mut bool,
+    ) {
+        self.dataset = dataset;
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                self.ui(ui);
+            });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.style_mut().override_text_style = Some(egui::style::TextStyle::Monospace);
+        use egui_extras::{Size, StripBuilder};
+        StripBuilder::new(ui)
+            .size(Size::remainder().at_least(100.0)) // for the bias table
+            .size(Size::exact(200.0)) // for the regression table
+            .vertical(|mut strip| {
+                strip.cell(|ui| {
+                    egui::ScrollArea::horizontal()
+                        .id_salt("bias_scroll")
+                        .show(ui, |ui| {
+                            self.bias_table_ui(ui);
+                        });
+                });
+                strip.cell(|ui| {
+                    egui::ScrollArea::horizontal()
+                        .id_salt("regression_scroll")
+                        .show(ui, |ui| {
+                            self.regression_table_ui(ui);
+                        });
+                });
+            });
+    }
+
+    fn bias_table_ui(&mut self, ui: &mut egui::Ui) {
+        use egui_extras::{Column, TableBuilder};
+
+        let table = TableBuilder::new(ui)
+            .id_salt("bias_table")
+            .striped(self.striped)
+            .cell_layout(egui::Layout::right_to_left(egui::Align::Center))
+            .columns(
+                Column::initial(90.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+                5,
+            )
+            .min_scrolled_height(0.0);
+
+        table
+            .header(20.0, |mut header| {
+                for title in ["Part", "Reference", "n", "Bias", "p"] {
+                    header.col(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.strong(title);
+                        });
+                    });
+                }
+            })
+            .body(|mut body| {
+                if let Some(study) = self.dataset {
+                    let row_height = 18.0;
+                    for r in study.references.iter() {
+                        body.row(row_height, |mut row| {
+                            row.col(|ui| {
+                                ui.label(r.part.clone());
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:>9.4}", r.reference));
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:>3}", r.count));
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:>9.5}", r.bias));
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:>6.3}", r.p_value));
+                            });
+                        });
+                    }
+                    body.row(row_height, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Average");
+                        });
+                        row.col(|ui| {
+                            ui.label("");
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{:>3}", study.points.len()));
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{:>9.5}", study.average_bias));
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{:>6.3}", study.average_bias_p));
+                        });
+                    });
+                }
+            });
+    }
+
+    fn regression_table_ui(&mut self, ui: &mut egui::Ui) {
+        use egui_extras::{Column, TableBuilder};
+
+        let table = TableBuilder::new(ui)
+            .id_salt("regression_table")
+            .striped(self.striped)
+            .cell_layout(egui::Layout::right_to_left(egui::Align::Center))
+            .columns(
+                Column::initial(100.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+                4,
+            )
+            .min_scrolled_height(0.0);
+
+        table
+            .header(20.0, |mut header| {
+                for title in ["Predictor", "Coef", "SE Coef", "p"] {
+                    header.col(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.strong(title);
+                        });
+                    });
+                }
+            })
+            .body(|mut body| {
+                if let Some(study) = self.dataset {
+                    let row_height = 18.0;
+                    let coefs = [
+                        (
+                            "Constant",
+                            study.intercept,
+                            study.intercept_se,
+                            study.intercept_p,
+                        ),
+                        ("Slope", study.slope, study.slope_se, study.slope_p),
+                    ];
+                    for (predictor, coef, se, p) in coefs.into_iter() {
+                        body.row(row_height, |mut row| {
+                            row.col(|ui| {
+                                ui.label(predictor);
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:>9.5}", coef));
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:>9.5}", se));
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:>6.3}", p));
+                            });
+                        });
+                    }
+                    let summary = [
+                        ("S", format!("{:>9.5}", study.s)),
+                        ("R-Sq (%)", format!("{:>6.2}", study.r_squared * 100.0)),
+                        ("Linearity", format!("{:>9.5}", study.linearity)),
+                        ("%Linearity", format!("{:>6.2}", study.percent_linearity)),
+                        ("%Bias", format!("{:>6.2}", study.percent_bias)),
+                    ];
+                    for (label, value) in summary.into_iter() {
+                        body.row(row_height, |mut row| {
+                            row.col(|ui| {
+                                ui.label(label);
+                            });
+                            row.col(|ui| {
+                                ui.label(value);
+                            });
+                            row.col(|ui| {
+                                ui.label("");
+                            });
+                            row.col(|ui| {
+                                ui.label("");
+                            });
+                        });
+                    }
+                }
+            });
+    }
+}
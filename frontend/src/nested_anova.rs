@@ -0,0 +1,181 @@
+use crate::statistics::f_test_p_value;
+use anyhow::{Result, bail};
+use gage_study::data::Data;
+use std::collections::BTreeMap;
+
+/// Nested (hierarchical) ANOVA for destructive testing, where each operator
+/// measures a different set of parts. Parts are identified by their id within
+/// an operator, so part labels may be reused across operators.
+pub struct NestedAnova {
+    pub operators: usize,
+    pub parts_per_operator: usize,
+    pub replicates: usize,
+    pub dof_operators: usize,
+    pub sumsq_operators: f64,
+    pub meansq_operators: f64,
+    pub f_operators: f64,
+    pub p_operators: f64,
+    pub dof_parts: usize,
+    pub sumsq_parts: f64,
+    pub meansq_parts: f64,
+    pub f_parts: f64,
+    pub p_parts: f64,
+    pub dof_repeatability: usize,
+    pub sumsq_repeatability: f64,
+    pub meansq_repeatability: f64,
+    pub dof_total: usize,
+    pub sumsq_total: f64,
+}
+
+impl NestedAnova {
+    pub fn from_data(data: &[Data]) -> Result<Self> {
+        let mut cells: BTreeMap<&str, BTreeMap<&str, Vec<f64>>> = BTreeMap::new();
+        for d in data.iter() {
+            cells
+                .entry(d.operator.as_str())
+                .or_default()
+                .entry(d.part.as_str())
+                .or_default()
+                .push(d.measured);
+        }
+        let operators = cells.len();
+        let parts_per_operator = cells.values().next().map_or(0, |p| p.len());
+        let replicates = cells
+            .values()
+            .next()
+            .and_then(|p| p.values().next())
+            .map_or(0, |v| v.len());
+        if operators < 2 || parts_per_operator < 2 || replicates < 2 {
+            bail!("Nested study requires at least two operators, parts and replicates");
+        }
+        let balanced = cells.values().all(|parts| {
+            parts.len() == parts_per_operator && parts.values().all(|v| v.len() == replicates)
+        });
+        if !balanced {
+            bail!(
+                "Nested study requires the same number of parts per operator and replicates per part"
+            );
+        }
+
+        let n = data.len() as f64;
+        let grand_mean = data.iter().map(|d| d.measured).sum::<f64>() / n;
+        let mut sumsq_operators = 0.0;
+        let mut sumsq_parts = 0.0;
+        let mut sumsq_repeatability = 0.0;
+        for parts in cells.values() {
+            let op_values = parts.values().flatten().copied().collect::<Vec<f64>>();
+            let op_mean = op_values.iter().sum::<f64>() / op_values.len() as f64;
+            sumsq_operators += op_values.len() as f64 * (op_mean - grand_mean).powi(2);
+            for values in parts.values() {
+                let part_mean = values.iter().sum::<f64>() / values.len() as f64;
+                sumsq_parts += values.len() as f64 * (part_mean - op_mean).powi(2);
+                sumsq_repeatability += values.iter().map(|v| (v - part_mean).powi(2)).sum::<f64>();
+            }
+        }
+        let sumsq_total = data
+            .iter()
+            .map(|d| (d.measured - grand_mean).powi(2))
+            .sum::<f64>();
+
+        let dof_operators = operators - 1;
+        let dof_parts = operators * (parts_per_operator - 1);
+        let dof_repeatability = operators * parts_per_operator * (replicates - 1);
+        let meansq_operators = sumsq_operators / dof_operators as f64;
+        let meansq_parts = sumsq_parts / dof_parts as f64;
+        let meansq_repeatability = sumsq_repeatability / dof_repeatability as f64;
+        // Operators are tested against parts within operators, parts against repeatability
+        let f_operators = meansq_operators / meansq_parts;
+        let f_parts = meansq_parts / meansq_repeatability;
+
+        Ok(Self {
+            operators,
+            parts_per_operator,
+            replicates,
+            dof_operators,
+            sumsq_operators,
+            meansq_operators,
+            f_operators,
+            p_operators: f_test_p_value(f_operators, dof_operators as f64, dof_parts as f64),
+            dof_parts,
+            sumsq_parts,
+            meansq_parts,
+            f_parts,
+            p_parts: f_test_p_value(f_parts, dof_parts as f64, dof_repeatability as f64),
+            dof_repeatability,
+            sumsq_repeatability,
+            meansq_repeatability,
+            dof_total: data.len() - 1,
+            sumsq_total,
+        })
+    }
+
+    /// Copy of `data` with part ids qualified by operator whenever a part label is
+    /// shared between operators, so plots do not merge physically different parts.
+    pub fn qualified_data(data: &[Data]) -> Vec<Data> {
+        let mut owners: BTreeMap<&str, &str> = BTreeMap::new();
+        let shared = data
+            .iter()
+            .any(|d| *owners.entry(d.part.as_str()).or_insert(d.operator.as_str()) != d.operator);
+        let mut qualified = data.to_owned();
+        if shared {
+            for d in qualified.iter_mut() {
+                d.part = format!("{}:{}", d.operator, d.part);
+            }
+        }
+        qualified
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{VarianceComponents, two_by_two_data};
+
+    #[test]
+    fn sums_of_squares_partition_the_total() {
+        // Read as two operators with two parts of their own, two replicates each
+        // Grand mean 5.5, operator means 4 and 7, part means 2, 6, 3 and 11
+        let anova = NestedAnova::from_data(&two_by_two_data()).unwrap();
+        assert!((anova.sumsq_operators - 18.0).abs() < 1e-12);
+        assert!((anova.sumsq_parts - 80.0).abs() < 1e-12);
+        assert!((anova.sumsq_repeatability - 8.0).abs() < 1e-12);
+        assert!((anova.sumsq_total - 106.0).abs() < 1e-12);
+        assert_eq!(
+            (
+                anova.dof_operators,
+                anova.dof_parts,
+                anova.dof_repeatability
+            ),
+            (1, 2, 4)
+        );
+        assert!((anova.f_operators - 0.45).abs() < 1e-12);
+        assert!((anova.f_parts - 20.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn variance_components_follow_expected_mean_squares() {
+        // Repeatability MSE = 2, parts (40 - 2) / 2 = 19, operators
+        // (18 - 40) / 4 = -5.5 which is set to zero
+        let anova = NestedAnova::from_data(&two_by_two_data()).unwrap();
+        let components = VarianceComponents::from_nested_anova(&anova);
+        assert!((components.total_gagerr.repeatability.varcomp - 2.0).abs() < 1e-12);
+        assert!((components.part_to_part.varcomp - 19.0).abs() < 1e-12);
+        assert_eq!(components.total_gagerr.reproducibility.varcomp, 0.0);
+        assert!(components.total_gagerr.reproducibility.adjusted);
+        assert_eq!(components.adjusted_sources(), vec!["Reproducibility"]);
+    }
+
+    #[test]
+    fn unbalanced_design_is_rejected() {
+        let mut data = two_by_two_data();
+        data.pop();
+        assert!(NestedAnova::from_data(&data).is_err());
+    }
+
+    #[test]
+    fn shared_part_labels_are_qualified() {
+        let qualified = NestedAnova::qualified_data(&two_by_two_data());
+        assert_eq!(qualified[0].part, "A:1");
+        assert_eq!(qualified[2].part, "B:1");
+    }
+}
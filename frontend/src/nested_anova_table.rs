@@ -0,0 +1,183 @@
+use crate::NestedAnova;
+use eframe::egui;
+/// Nested ANOVA table with parts nested within operators
+pub struct NestedAnovaTableView<'a> {
+    pub striped: bool,
+    pub resizable: bool,
+    pub dataset: &'a Option<NestedAnova>,
+}
+
+impl Default for NestedAnovaTableView<'_> {
+    fn default() -> Self {
+        Self {
+            striped: true,
+            resizable: true,
+            dataset: &None,
+        }
+    }
+}
+
+impl<'a> NestedAnovaTableView<'a> {
+    pub fn name(&self) -> &'static str {
+        "☰ Nested Anova Table"
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, dataset: &'a Option<NestedAnova>, open: &mut bool) {
+        self.dataset = dataset;
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                self.ui(ui);
+            });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.style_mut().override_text_style = Some(egui::style::TextStyle::Monospace);
+        use egui_extras::{Size, StripBuilder};
+        StripBuilder::new(ui)
+            .size(Size::remainder().at_least(100.0)) // for the table
+            .vertical(|mut strip| {
+                strip.cell(|ui| {
+                    egui::ScrollArea::horizontal().show(ui, |ui| {
+                        self.table_ui(ui);
+                    });
+                });
+            });
+    }
+
+    fn table_ui(&mut self, ui: &mut egui::Ui) {
+        use egui_extras::{Column, TableBuilder};
+
+        let table = TableBuilder::new(ui)
+            .striped(self.striped)
+            .cell_layout(egui::Layout::right_to_left(egui::Align::Max))
+            .column(
+                Column::initial(100.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+            )
+            .column(
+                Column::initial(100.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+            )
+            .column(
+                Column::initial(100.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+            )
+            .column(
+                Column::initial(100.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+            )
+            .column(
+                Column::initial(100.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+            )
+            .column(
+                Column::initial(100.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+            )
+            .min_scrolled_height(0.0);
+
+        table
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.strong("Source");
+                    });
+                });
+                header.col(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.strong("df");
+                    });
+                });
+                header.col(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.strong("SS");
+                    });
+                });
+                header.col(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.strong("MS");
+                    });
+                });
+                header.col(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.strong("F");
+                    });
+                });
+                header.col(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.strong("p");
+                    });
+                });
+            })
+            .body(|mut body| {
+                if let Some(anova) = self.dataset {
+                    let row_height = 18.0;
+                    let rows = [
+                        (
+                            "Operator",
+                            anova.dof_operators,
+                            anova.sumsq_operators,
+                            Some(anova.meansq_operators),
+                            Some((anova.f_operators, anova.p_operators)),
+                        ),
+                        (
+                            "Part(Operator)",
+                            anova.dof_parts,
+                            anova.sumsq_parts,
+                            Some(anova.meansq_parts),
+                            Some((anova.f_parts, anova.p_parts)),
+                        ),
+                        (
+                            "Repeatability",
+                            anova.dof_repeatability,
+                            anova.sumsq_repeatability,
+                            Some(anova.meansq_repeatability),
+                            None,
+                        ),
+                        ("Total", anova.dof_total, anova.sumsq_total, None, None),
+                    ];
+                    for (source, dof, sumsq, meansq, test) in rows.into_iter() {
+                        body.row(row_height, |mut row| {
+                            row.col(|ui| {
+                                ui.label(format!("{:<15}", source));
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:>3}", dof));
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:>9.5}", sumsq));
+                            });
+                            row.col(|ui| {
+                                ui.label(meansq.map_or(String::new(), |v| format!("{:>9.5}", v)));
+                            });
+                            row.col(|ui| {
+                                ui.label(
+                                    test.map_or(String::new(), |(f, _)| format!("{:>9.5}", f)),
+                                );
+                            });
+                            row.col(|ui| {
+                                ui.label(
+                                    test.map_or(String::new(), |(_, p)| format!("{:>6.3}", p)),
+                                );
+                            });
+                        });
+                    }
+                }
+            });
+    }
+}
@@ -0,0 +1,144 @@
+use crate::statistics::{a2, d3, d4};
+use anyhow::{Result, bail};
+use gage_study::data::Data;
+use std::collections::BTreeMap;
+
+/// Replicate readings of one part by one operator
+pub struct Subgroup {
+    pub operator: String,
+    pub part: String,
+    pub mean: f64,
+    pub range: f64,
+    /// Indices of the readings in the data the chart was built from
+    pub rows: Vec<usize>,
+}
+
+/// Subgroup statistics for the AIAG control charts by operator, with limits from
+/// the average range. Subgroups are ordered by operator, then part.
+pub struct OperatorChart {
+    pub operators: Vec<String>,
+    pub subgroups: Vec<Subgroup>,
+    /// Readings per subgroup; the smallest when the study is unbalanced
+    pub replicates: usize,
+    pub grand_mean: f64,
+    pub average_range: f64,
+    pub xbar_ucl: f64,
+    pub xbar_lcl: f64,
+    pub range_ucl: f64,
+    pub range_lcl: f64,
+}
+
+impl OperatorChart {
+    pub fn from_data(data: &[Data]) -> Result<Self> {
+        let mut cells = BTreeMap::<(&str, &str), Vec<usize>>::new();
+        for (i, d) in data.iter().enumerate() {
+            cells
+                .entry((d.operator.as_str(), d.part.as_str()))
+                .or_default()
+                .push(i);
+        }
+        let replicates = cells.values().map(Vec::len).min().unwrap_or(0);
+        if replicates < 2 {
+            bail!("Control charts by operator require at least two replicates of each part");
+        }
+        let subgroups = cells
+            .into_iter()
+            .map(|((operator, part), rows)| {
+                let values = rows.iter().map(|i| data[*i].measured).collect::<Vec<f64>>();
+                let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+                Subgroup {
+                    operator: operator.to_owned(),
+                    part: part.to_owned(),
+                    mean: values.iter().sum::<f64>() / values.len() as f64,
+                    range: max - min,
+                    rows,
+                }
+            })
+            .collect::<Vec<Subgroup>>();
+        let mut operators = subgroups
+            .iter()
+            .map(|s| s.operator.clone())
+            .collect::<Vec<String>>();
+        operators.dedup();
+        let count = subgroups.len() as f64;
+        let grand_mean = subgroups.iter().map(|s| s.mean).sum::<f64>() / count;
+        let average_range = subgroups.iter().map(|s| s.range).sum::<f64>() / count;
+        let spread = a2(replicates) * average_range;
+        Ok(Self {
+            operators,
+            subgroups,
+            replicates,
+            grand_mean,
+            average_range,
+            xbar_ucl: grand_mean + spread,
+            xbar_lcl: grand_mean - spread,
+            range_ucl: d4(replicates) * average_range,
+            range_lcl: d3(replicates) * average_range,
+        })
+    }
+
+    pub fn xbar_outside(&self, subgroup: &Subgroup) -> bool {
+        subgroup.mean > self.xbar_ucl || subgroup.mean < self.xbar_lcl
+    }
+
+    /// Number of part averages outside the Xbar limits. The limits reflect gage
+    /// variation only, so a gage that discriminates between parts puts more than
+    /// half of the averages outside them.
+    pub fn xbar_outside_count(&self) -> usize {
+        self.subgroups
+            .iter()
+            .filter(|s| self.xbar_outside(s))
+            .count()
+    }
+
+    pub fn range_outside(&self, subgroup: &Subgroup) -> bool {
+        subgroup.range > self.range_ucl || subgroup.range < self.range_lcl
+    }
+
+    /// Average range of each operator's subgroups
+    pub fn operator_average_ranges(&self) -> Vec<(&str, f64)> {
+        self.operators
+            .iter()
+            .map(|operator| {
+                let ranges = self
+                    .subgroups
+                    .iter()
+                    .filter(|s| &s.operator == operator)
+                    .map(|s| s.range)
+                    .collect::<Vec<f64>>();
+                (
+                    operator.as_str(),
+                    ranges.iter().sum::<f64>() / ranges.len() as f64,
+                )
+            })
+            .collect()
+    }
+
+    /// Operators with out-of-control ranges: their repeatability differs from the
+    /// others', often from a different measurement technique
+    pub fn inconsistent_operators(&self) -> Vec<&str> {
+        self.operators
+            .iter()
+            .filter(|operator| {
+                self.subgroups
+                    .iter()
+                    .any(|s| &s.operator == *operator && self.range_outside(s))
+            })
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Plot position of each subgroup, leaving a gap between operators
+    pub fn positions(&self) -> Vec<f64> {
+        let mut positions = Vec::with_capacity(self.subgroups.len());
+        let mut gap = 0.0;
+        for (i, subgroup) in self.subgroups.iter().enumerate() {
+            if i > 0 && self.subgroups[i - 1].operator != subgroup.operator {
+                gap += 1.0;
+            }
+            positions.push(i as f64 + 1.0 + gap);
+        }
+        positions
+    }
+}
@@ -0,0 +1,225 @@
+use crate::statistics::t_critical;
+use crate::{OperatorChart, Residuals};
+use anyhow::Result;
+use gage_study::data::Data;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Significance level of Grubbs' test within a part/operator cell
+const GRUBBS_ALPHA: f64 = 0.05;
+/// Studentized residuals beyond this are flagged
+const STUDENTIZED_LIMIT: f64 = 3.0;
+
+/// Why a reading is suspect
+pub enum OutlierReason {
+    /// Grubbs' statistic of the reading within its cell exceeds the critical value
+    Grubbs {
+        g: f64,
+        critical: f64,
+    },
+    /// The reading is the extreme of a cell whose range exceeds UCL_R
+    RangeAboveUcl {
+        range: f64,
+        ucl: f64,
+    },
+    StudentizedResidual(f64),
+}
+
+impl fmt::Display for OutlierReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Grubbs { g, critical } => {
+                write!(f, "Grubbs G = {g:.3} > {critical:.3}")
+            }
+            Self::RangeAboveUcl { range, ucl } => {
+                write!(f, "cell range {range:.4} > UCL_R {ucl:.4}")
+            }
+            Self::StudentizedResidual(r) => write!(f, "studentized residual {r:.2}"),
+        }
+    }
+}
+
+pub struct Outlier {
+    /// Index of the reading in the data that was screened
+    pub row: usize,
+    pub reasons: Vec<OutlierReason>,
+}
+
+impl Outlier {
+    /// Reasons the reading was flagged, one per line, for tooltips
+    pub fn describe(&self) -> String {
+        self.reasons
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// Screening of replicate readings for keying errors and other outliers that
+/// would otherwise dominate the repeatability estimate
+pub struct OutlierScreen {
+    /// Suspect readings in row order
+    pub outliers: Vec<Outlier>,
+}
+
+impl OutlierScreen {
+    pub fn from_data(data: &[Data]) -> Result<Self> {
+        let mut reasons = BTreeMap::<usize, Vec<OutlierReason>>::new();
+
+        let mut cells = BTreeMap::<(&str, &str), Vec<usize>>::new();
+        for (i, d) in data.iter().enumerate() {
+            cells
+                .entry((d.part.as_str(), d.operator.as_str()))
+                .or_default()
+                .push(i);
+        }
+        for rows in cells.values() {
+            if let Some((row, g, critical)) = grubbs(data, rows) {
+                reasons
+                    .entry(row)
+                    .or_default()
+                    .push(OutlierReason::Grubbs { g, critical });
+            }
+        }
+
+        // Charts need two replicates in every cell, without them there is no UCL_R
+        if let Ok(chart) = OperatorChart::from_data(data) {
+            for subgroup in chart.subgroups.iter() {
+                if subgroup.range <= chart.range_ucl {
+                    continue;
+                }
+                for row in extremes(data, &subgroup.rows) {
+                    reasons
+                        .entry(row)
+                        .or_default()
+                        .push(OutlierReason::RangeAboveUcl {
+                            range: subgroup.range,
+                            ucl: chart.range_ucl,
+                        });
+                }
+            }
+        }
+
+        let residuals = Residuals::from_data(data)?;
+        for (row, r) in residuals.studentized.iter().enumerate() {
+            if r.abs() > STUDENTIZED_LIMIT {
+                reasons
+                    .entry(row)
+                    .or_default()
+                    .push(OutlierReason::StudentizedResidual(*r));
+            }
+        }
+
+        Ok(Self {
+            outliers: reasons
+                .into_iter()
+                .map(|(row, reasons)| Outlier { row, reasons })
+                .collect(),
+        })
+    }
+
+    pub fn rows(&self) -> Vec<usize> {
+        self.outliers.iter().map(|o| o.row).collect()
+    }
+}
+
+/// Grubbs' two-sided test for a single outlier among the readings `rows`,
+/// returning the suspect row with its statistic and critical value
+fn grubbs(data: &[Data], rows: &[usize]) -> Option<(usize, f64, f64)> {
+    let n = rows.len();
+    if n < 3 {
+        return None;
+    }
+    let nf = n as f64;
+    let mean = rows.iter().map(|i| data[*i].measured).sum::<f64>() / nf;
+    let stddev = (rows
+        .iter()
+        .map(|i| (data[*i].measured - mean).powi(2))
+        .sum::<f64>()
+        / (nf - 1.0))
+        .sqrt();
+    if stddev <= 0.0 {
+        return None;
+    }
+    let (row, deviation) = rows
+        .iter()
+        .map(|i| (*i, (data[*i].measured - mean).abs()))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    let g = deviation / stddev;
+    let critical = grubbs_critical(n);
+    (g > critical).then_some((row, g, critical))
+}
+
+/// Two-sided critical value of Grubbs' statistic for `n` readings
+fn grubbs_critical(n: usize) -> f64 {
+    let nf = n as f64;
+    let t = t_critical(GRUBBS_ALPHA / nf, nf - 2.0);
+    (nf - 1.0) / nf.sqrt() * (t.powi(2) / (nf - 2.0 + t.powi(2))).sqrt()
+}
+
+/// Readings farthest from the median of `rows`: both readings of a pair, which
+/// cannot be told apart, otherwise the one pulling the range out
+fn extremes(data: &[Data], rows: &[usize]) -> Vec<usize> {
+    let mut values = rows.iter().map(|i| data[*i].measured).collect::<Vec<f64>>();
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    let median = if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    };
+    let farthest = values
+        .iter()
+        .map(|v| (v - median).abs())
+        .fold(0.0, f64::max);
+    rows.iter()
+        .copied()
+        .filter(|i| ((data[*i].measured - median).abs() - farthest).abs() <= farthest * 1e-9)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::make_data;
+
+    #[test]
+    fn grubbs_critical_values() {
+        // Two-sided critical values at the 5% level from published tables
+        for (n, expected) in [(3, 1.1543), (5, 1.7150), (10, 2.2900), (20, 2.7082)] {
+            assert!(
+                (grubbs_critical(n) - expected).abs() < 1e-3,
+                "n = {n}: {}",
+                grubbs_critical(n)
+            );
+        }
+    }
+
+    #[test]
+    fn flags_keying_error() {
+        let values = [10.1, 10.2, 10.0, 10.1, 11.0, 10.2, 10.1, 10.0, 10.2, 10.1];
+        let data = values
+            .iter()
+            .enumerate()
+            .map(|(i, measured)| make_data("Study", "P1", "A", i + 1, *measured, 0.0).unwrap())
+            .collect::<Vec<Data>>();
+        let screen = OutlierScreen::from_data(&data).unwrap();
+        assert_eq!(screen.rows(), vec![4]);
+        assert!(matches!(
+            screen.outliers[0].reasons[0],
+            OutlierReason::Grubbs { .. }
+        ));
+    }
+
+    #[test]
+    fn consistent_readings_are_not_flagged() {
+        let values = [10.1, 10.2, 10.0, 10.1, 10.3, 10.2];
+        let data = values
+            .iter()
+            .enumerate()
+            .map(|(i, measured)| make_data("Study", "P1", "A", i + 1, *measured, 0.0).unwrap())
+            .collect::<Vec<Data>>();
+        assert!(OutlierScreen::from_data(&data).unwrap().outliers.is_empty());
+    }
+}
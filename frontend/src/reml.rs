@@ -0,0 +1,351 @@
+use anyhow::{Result, bail};
+use gage_study::data::Data;
+use std::collections::{BTreeMap, BTreeSet};
+
+const MAX_ITERATIONS: usize = 200;
+const TOLERANCE: f64 = 1e-10;
+
+/// Restricted maximum likelihood estimates of the variance components of a
+/// crossed random-effects model (part, operator, part*operator, error). Unlike
+/// the expected-mean-squares ANOVA it remains valid for unbalanced studies.
+pub struct Reml {
+    pub part: f64,
+    pub operator: f64,
+    pub part_operator: f64,
+    pub repeatability: f64,
+    pub log_likelihood: f64,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+impl Reml {
+    /// True when every part/operator combination has the same number of readings
+    pub fn is_balanced(data: &[Data]) -> bool {
+        let mut cells: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+        for d in data.iter() {
+            *cells
+                .entry((d.part.as_str(), d.operator.as_str()))
+                .or_insert(0) += 1;
+        }
+        let parts = cells.keys().map(|k| k.0).collect::<BTreeSet<&str>>();
+        let operators = cells.keys().map(|k| k.1).collect::<BTreeSet<&str>>();
+        let per_cell = cells.values().next().copied().unwrap_or(0);
+        cells.len() == parts.len() * operators.len() && cells.values().all(|c| *c == per_cell)
+    }
+
+    pub fn from_data(data: &[Data]) -> Result<Self> {
+        let n = data.len();
+        if n < 4 {
+            bail!("REML estimation requires at least four measurements");
+        }
+        // Readings within a part/operator cell differ only by error, so the
+        // likelihood splits into the cell means and the within-cell sum of
+        // squares. Working on the cells keeps the matrices at cells × cells.
+        let mut sums: BTreeMap<(&str, &str), (usize, f64)> = BTreeMap::new();
+        for d in data.iter() {
+            let cell = sums
+                .entry((d.part.as_str(), d.operator.as_str()))
+                .or_insert((0, 0.0));
+            cell.0 += 1;
+            cell.1 += d.measured;
+        }
+        let within_ss = data
+            .iter()
+            .map(|d| {
+                let (count, sum) = sums[&(d.part.as_str(), d.operator.as_str())];
+                (d.measured - sum / count as f64).powi(2)
+            })
+            .sum::<f64>();
+        let within_dof = (n - sums.len()) as f64;
+        let cells = sums
+            .iter()
+            .map(|((part, operator), (count, sum))| Cell {
+                part,
+                operator,
+                count: *count as f64,
+                mean: sum / *count as f64,
+            })
+            .collect::<Vec<Cell<'_>>>();
+        let m = cells.len();
+        // Cell means scaled by sqrt(count), whose mean model is x * mu
+        let x = cells.iter().map(|c| c.count.sqrt()).collect::<Vec<f64>>();
+        let z = cells
+            .iter()
+            .map(|c| c.count.sqrt() * c.mean)
+            .collect::<Vec<f64>>();
+        // Covariance of z per unit of each component: part, operator,
+        // part*operator and error
+        let shared = |same: &dyn Fn(&Cell<'_>, &Cell<'_>) -> bool| {
+            (0..m)
+                .map(|i| {
+                    (0..m)
+                        .map(|j| {
+                            if same(&cells[i], &cells[j]) {
+                                x[i] * x[j]
+                            } else {
+                                0.0
+                            }
+                        })
+                        .collect::<Vec<f64>>()
+                })
+                .collect::<Vec<Vec<f64>>>()
+        };
+        let diagonal = |values: &dyn Fn(usize) -> f64| {
+            (0..m)
+                .map(|i| {
+                    (0..m)
+                        .map(|j| if i == j { values(i) } else { 0.0 })
+                        .collect()
+                })
+                .collect::<Vec<Vec<f64>>>()
+        };
+        let components = [
+            shared(&|a, b| a.part == b.part),
+            shared(&|a, b| a.operator == b.operator),
+            diagonal(&|i| cells[i].count),
+            diagonal(&|_| 1.0),
+        ];
+
+        // Start from the total variance split evenly
+        let mean = data.iter().map(|d| d.measured).sum::<f64>() / n as f64;
+        let variance = data
+            .iter()
+            .map(|d| (d.measured - mean).powi(2))
+            .sum::<f64>()
+            / (n - 1) as f64;
+        if variance <= 0.0 {
+            bail!("REML estimation requires variation in the measurements");
+        }
+        let floor = variance * 1e-8;
+        let mut theta = [variance / 4.0; 4];
+        let mut log_likelihood = f64::NEG_INFINITY;
+        let mut converged = false;
+        let mut iterations = 0;
+
+        while iterations < MAX_ITERATIONS {
+            iterations += 1;
+            let mut v = vec![vec![0.0; m]; m];
+            for (component, t) in components.iter().zip(theta.iter()) {
+                for (row, c_row) in v.iter_mut().zip(component.iter()) {
+                    for (value, c) in row.iter_mut().zip(c_row.iter()) {
+                        *value += t * c;
+                    }
+                }
+            }
+            let Some((p, log_det, x_v_x)) = projection(v, &x) else {
+                bail!("REML estimation failed: covariance matrix is not positive definite");
+            };
+            let pz = mat_vec(&p, &z);
+            let error = theta[3];
+            // P V_k for each component
+            let pv = components
+                .iter()
+                .map(|c| mat_mul(&p, c))
+                .collect::<Vec<Vec<Vec<f64>>>>();
+            let mut score = [0.0; 4];
+            let mut info = [[0.0; 4]; 4];
+            for k in 0..4 {
+                let trace = (0..m).map(|i| pv[k][i][i]).sum::<f64>();
+                let quad = pz
+                    .iter()
+                    .zip(mat_vec(&components[k], &pz).iter())
+                    .map(|(a, b)| a * b)
+                    .sum::<f64>();
+                score[k] = 0.5 * (quad - trace);
+                for l in 0..=k {
+                    let tr = (0..m)
+                        .map(|i| (0..m).map(|j| pv[k][i][j] * pv[l][j][i]).sum::<f64>())
+                        .sum::<f64>();
+                    info[k][l] = 0.5 * tr;
+                    info[l][k] = 0.5 * tr;
+                }
+            }
+            // Within-cell deviations carry information on the error alone
+            score[3] += 0.5 * (within_ss / error.powi(2) - within_dof / error);
+            info[3][3] += 0.5 * within_dof / error.powi(2);
+            let ll = -0.5
+                * (log_det
+                    + x_v_x.ln()
+                    + z.iter().zip(pz.iter()).map(|(a, b)| a * b).sum::<f64>()
+                    + within_dof * error.ln()
+                    + within_ss / error);
+
+            // Fisher scoring step, keeping the components non-negative
+            let Some(step) = solve(info, score) else {
+                bail!("REML estimation failed: information matrix is singular");
+            };
+            let mut change = 0.0_f64;
+            for k in 0..4 {
+                let next = (theta[k] + step[k]).max(if k == 3 { floor } else { 0.0 });
+                change = change.max((next - theta[k]).abs());
+                theta[k] = next;
+            }
+            let previous = log_likelihood;
+            log_likelihood = ll;
+            if change < TOLERANCE * variance || (ll - previous).abs() < TOLERANCE {
+                converged = true;
+                break;
+            }
+        }
+
+        Ok(Self {
+            part: theta[0],
+            operator: theta[1],
+            part_operator: theta[2],
+            repeatability: theta[3],
+            log_likelihood,
+            iterations,
+            converged,
+        })
+    }
+}
+
+/// Readings of one part/operator combination
+struct Cell<'a> {
+    part: &'a str,
+    operator: &'a str,
+    count: f64,
+    mean: f64,
+}
+
+/// REML projection matrix P = V^-1 - V^-1 x (x' V^-1 x)^-1 x' V^-1, with the
+/// log-determinant of V and x' V^-1 x for the restricted log-likelihood
+fn projection(v: Vec<Vec<f64>>, x: &[f64]) -> Option<(Vec<Vec<f64>>, f64, f64)> {
+    let m = x.len();
+    let (v_inv, log_det) = cholesky_inverse(v)?;
+    let v_inv_x = mat_vec(&v_inv, x);
+    let denom = x
+        .iter()
+        .zip(v_inv_x.iter())
+        .map(|(a, b)| a * b)
+        .sum::<f64>();
+    let p = (0..m)
+        .map(|i| {
+            (0..m)
+                .map(|j| v_inv[i][j] - v_inv_x[i] * v_inv_x[j] / denom)
+                .collect::<Vec<f64>>()
+        })
+        .collect::<Vec<Vec<f64>>>();
+    Some((p, log_det, denom))
+}
+
+fn mat_vec(m: &[Vec<f64>], v: &[f64]) -> Vec<f64> {
+    m.iter()
+        .map(|row| row.iter().zip(v.iter()).map(|(a, b)| a * b).sum())
+        .collect()
+}
+
+fn mat_mul(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    a.iter()
+        .map(|row| {
+            let mut product = vec![0.0; b.first().map_or(0, Vec::len)];
+            for (x, b_row) in row.iter().zip(b.iter()) {
+                for (value, y) in product.iter_mut().zip(b_row.iter()) {
+                    *value += x * y;
+                }
+            }
+            product
+        })
+        .collect()
+}
+
+/// Inverse and log-determinant of a symmetric positive definite matrix
+fn cholesky_inverse(mut a: Vec<Vec<f64>>) -> Option<(Vec<Vec<f64>>, f64)> {
+    let n = a.len();
+    // In-place lower triangular factor L with A = L L'
+    for j in 0..n {
+        let mut d = a[j][j] - (0..j).map(|k| a[j][k] * a[j][k]).sum::<f64>();
+        if d <= 0.0 {
+            return None;
+        }
+        d = d.sqrt();
+        a[j][j] = d;
+        for i in (j + 1)..n {
+            let s = a[i][j] - (0..j).map(|k| a[i][k] * a[j][k]).sum::<f64>();
+            a[i][j] = s / d;
+        }
+    }
+    let log_det = 2.0 * (0..n).map(|i| a[i][i].ln()).sum::<f64>();
+    // Invert L, then A^-1 = L^-T L^-1
+    let mut l_inv = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        l_inv[i][i] = 1.0 / a[i][i];
+        let row = (0..i)
+            .map(|j| -(j..i).map(|k| a[i][k] * l_inv[k][j]).sum::<f64>() / a[i][i])
+            .collect::<Vec<f64>>();
+        l_inv[i][..i].copy_from_slice(&row);
+    }
+    let inv = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| {
+                    (i.max(j)..n)
+                        .map(|k| l_inv[k][i] * l_inv[k][j])
+                        .sum::<f64>()
+                })
+                .collect::<Vec<f64>>()
+        })
+        .collect::<Vec<Vec<f64>>>();
+    Some((inv, log_det))
+}
+
+/// Solve a small linear system by Gaussian elimination with partial pivoting
+fn solve<const N: usize>(mut a: [[f64; N]; N], mut b: [f64; N]) -> Option<[f64; N]> {
+    for col in 0..N {
+        let pivot = (col..N).max_by(|i, j| a[*i][col].abs().total_cmp(&a[*j][col].abs()))?;
+        if a[pivot][col].abs() < f64::EPSILON {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let (upper, lower) = a.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for (offset, row) in lower.iter_mut().enumerate() {
+            let factor = row[col] / pivot_row[col];
+            for (value, p) in row.iter_mut().zip(pivot_row.iter()).skip(col) {
+                *value -= factor * p;
+            }
+            b[col + 1 + offset] -= factor * b[col];
+        }
+    }
+    let mut x = [0.0; N];
+    for row in (0..N).rev() {
+        let s = ((row + 1)..N).map(|k| a[row][k] * x[k]).sum::<f64>();
+        x[row] = (b[row] - s) / a[row][row];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{make_data, two_by_two_data};
+
+    #[test]
+    fn balanced_design_matches_expected_mean_squares() {
+        // REML equals the ANOVA estimates when none of them is negative
+        let reml = Reml::from_data(&two_by_two_data()).unwrap();
+        assert!(reml.converged);
+        assert!((reml.part - 16.0).abs() < 1e-6);
+        assert!((reml.operator - 2.5).abs() < 1e-6);
+        assert!((reml.part_operator - 3.0).abs() < 1e-6);
+        assert!((reml.repeatability - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn unbalanced_design_is_detected_and_estimated() {
+        let mut data = two_by_two_data();
+        assert!(Reml::is_balanced(&data));
+        data.push(make_data("", "2", "B", 3, 11.0, 0.0).unwrap());
+        assert!(!Reml::is_balanced(&data));
+        let reml = Reml::from_data(&data).unwrap();
+        assert!(reml.converged);
+        // Maximum of the restricted likelihood over the full 9 x 9 covariance
+        // of the readings; the extra reading sits at its cell mean, shrinking
+        // the error estimate
+        assert!((reml.part - 16.26085).abs() < 1e-4);
+        assert!((reml.operator - 2.56521).abs() < 1e-4);
+        assert!((reml.part_operator - 3.40348).abs() < 1e-4);
+        assert!((reml.repeatability - 1.58761).abs() < 1e-4);
+    }
+}
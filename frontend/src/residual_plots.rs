@@ -0,0 +1,254 @@
+use crate::{
+    BarSummary, ExportSettings, Figure, Mark, Marker, OutlierScreen, PlotOutput, Residuals,
+};
+use eframe::egui;
+use gage_study::data::Data;
+
+#[derive(Default)]
+pub struct ResidualPlots<'a> {
+    pub residuals: Option<&'a Residuals>,
+    /// Readings the residuals were calculated from, for tooltips
+    pub data: &'a [Data],
+    /// Rows flagged as suspect readings
+    pub outliers: Vec<usize>,
+    /// Rows selected in the data table or any plot
+    pub selection: Vec<usize>,
+}
+
+impl<'a> ResidualPlots<'a> {
+    pub fn name(&self) -> &'static str {
+        "☰ Residual Diagnostics"
+    }
+
+    pub fn with_data(mut self, data: &'a [Data]) -> Self {
+        self.data = data;
+        self
+    }
+
+    pub fn with_outliers(mut self, screen: &Option<OutlierScreen>) -> Self {
+        if let Some(screen) = screen {
+            self.outliers = screen.rows();
+        }
+        self
+    }
+
+    pub fn with_selection(mut self, selection: &[usize]) -> Self {
+        self.selection = selection.to_owned();
+        self
+    }
+
+    /// Shows the diagnostics, returning the rows of clicked or brushed points and
+    /// any exported image
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        residuals: &'a Option<Residuals>,
+        open: &mut bool,
+    ) -> PlotOutput {
+        self.residuals = residuals.as_ref();
+        let mut output = PlotOutput::default();
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(700.0)
+            .default_height(600.0)
+            .show(ctx, |ui| {
+                output = self.ui(ui);
+            });
+        output
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> PlotOutput {
+        let mut output = PlotOutput::default();
+        let Some(residuals) = self.residuals else {
+            return output;
+        };
+        ui.label(format!(
+            "Anderson-Darling A² = {:.4}, p-value = {:.4}",
+            residuals.anderson_darling, residuals.p_value
+        ));
+        let row_height = (ui.available_height() - ui.spacing().item_spacing.y) / 2.0;
+        let mut figures = [
+            (
+                "residual_normal_probability",
+                normal_probability_plot(residuals, &self.outliers),
+            ),
+            (
+                "residual_versus_fitted",
+                versus_fitted(residuals, &self.outliers),
+            ),
+            (
+                "residual_versus_order",
+                versus_order(residuals, &self.outliers),
+            ),
+            ("residual_histogram", histogram(residuals)),
+        ];
+        for pair in figures.chunks_mut(2) {
+            ui.columns(2, |columns| {
+                for (ui, (id, figure)) in columns.iter_mut().zip(pair.iter_mut()) {
+                    let top = ui.cursor().top();
+                    if let Some(export) = ExportSettings::ui(ui, id, figure) {
+                        output.export = Some(export);
+                    }
+                    // The export settings take their share of the row
+                    figure.height = Some(row_height - (ui.cursor().top() - top));
+                    if let Some(rows) = figure.show(ui, id, self.data, &self.selection) {
+                        output.selected = Some(rows);
+                    }
+                }
+            });
+        }
+        output
+    }
+}
+
+/// Residuals against their normal scores, with the line a normal distribution
+/// of the same mean and standard deviation would follow
+fn normal_probability_plot(residuals: &Residuals, outliers: &[usize]) -> Figure {
+    let points = residuals
+        .residuals
+        .iter()
+        .zip(residuals.normal_scores.iter())
+        .map(|(r, z)| [*r, *z])
+        .collect::<Vec<[f64; 2]>>();
+    let n = residuals.residuals.len() as f64;
+    let mean = residuals.residuals.iter().sum::<f64>() / n;
+    let stddev = (residuals
+        .residuals
+        .iter()
+        .map(|r| (r - mean).powi(2))
+        .sum::<f64>()
+        / (n - 1.0))
+        .sqrt();
+    let min = residuals
+        .residuals
+        .iter()
+        .copied()
+        .fold(f64::INFINITY, f64::min);
+    let max = residuals
+        .residuals
+        .iter()
+        .copied()
+        .fold(f64::NEG_INFINITY, f64::max);
+    let mut figure = Figure::new("Normal Probability Plot", "Residual", "Normal score");
+    if stddev > 0.0 {
+        figure.push(
+            "normal",
+            egui::Color32::RED,
+            Mark::Line {
+                points: vec![[min, (min - mean) / stddev], [max, (max - mean) / stddev]],
+                highlight: false,
+                dashed: false,
+            },
+        );
+    }
+    push_residuals(&mut figure, points, outliers);
+    figure
+}
+
+fn versus_fitted(residuals: &Residuals, outliers: &[usize]) -> Figure {
+    let points = residuals
+        .fitted
+        .iter()
+        .zip(residuals.residuals.iter())
+        .map(|(fit, r)| [*fit, *r])
+        .collect::<Vec<[f64; 2]>>();
+    let mut figure = Figure::new("Versus Fits", "Fitted value", "Residual");
+    figure.push("zero", egui::Color32::DARK_GREEN, zero());
+    push_residuals(&mut figure, points, outliers);
+    figure
+}
+
+fn versus_order(residuals: &Residuals, outliers: &[usize]) -> Figure {
+    let points = residuals
+        .residuals
+        .iter()
+        .enumerate()
+        .map(|(i, r)| [i as f64 + 1.0, *r])
+        .collect::<Vec<[f64; 2]>>();
+    let mut figure = Figure::new("Versus Order", "Observation order", "Residual");
+    figure.push("zero", egui::Color32::DARK_GREEN, zero());
+    figure.push(
+        "residuals",
+        egui::Color32::BLUE,
+        Mark::Line {
+            points: points.clone(),
+            highlight: false,
+            dashed: false,
+        },
+    );
+    push_residuals(&mut figure, points, outliers);
+    figure
+}
+
+/// Dashed line at zero residual
+fn zero() -> Mark {
+    Mark::HLine {
+        y: 0.0,
+        width: 1.0,
+        dashed: true,
+    }
+}
+
+/// Residual points, which are in row order, with the rows in `outliers` marked
+fn push_residuals(figure: &mut Figure, points: Vec<[f64; 2]>, outliers: &[usize]) {
+    let suspect = outliers
+        .iter()
+        .filter_map(|row| points.get(*row).map(|point| (*point, vec![*row])))
+        .unzip::<_, _, Vec<[f64; 2]>, Vec<Vec<usize>>>();
+    let rows = (0..points.len()).map(|row| vec![row]).collect();
+    figure.push(
+        "residuals",
+        egui::Color32::BLUE,
+        Mark::Points {
+            points,
+            marker: Marker::Circle,
+            radius: 3.0,
+            filled: true,
+            highlight: false,
+            rows,
+        },
+    );
+    figure.push(
+        "suspect reading",
+        egui::Color32::RED,
+        Mark::Points {
+            points: suspect.0,
+            marker: Marker::Square,
+            radius: 5.0,
+            filled: true,
+            highlight: false,
+            rows: suspect.1,
+        },
+    );
+}
+
+fn histogram(residuals: &Residuals) -> Figure {
+    let mut figure = Figure::new("Histogram", "Residual", "Frequency");
+    let values = &residuals.residuals;
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if values.is_empty() || !min.is_finite() || !max.is_finite() {
+        return figure;
+    }
+    // Square-root rule for the number of bins
+    let bins = ((values.len() as f64).sqrt().ceil() as usize).clamp(5, 30);
+    let width = ((max - min) / bins as f64).max(f64::EPSILON);
+    let mut counts = vec![0usize; bins];
+    for value in values.iter() {
+        let bin = (((value - min) / width) as usize).min(bins - 1);
+        counts[bin] += 1;
+    }
+    let bars = counts
+        .iter()
+        .enumerate()
+        .map(|(i, count)| BarSummary {
+            x: min + (i as f64 + 0.5) * width,
+            height: *count as f64,
+            width,
+            name: String::new(),
+        })
+        .collect();
+    figure.push("residuals", egui::Color32::LIGHT_BLUE, Mark::Bars(bars));
+    figure
+}
@@ -0,0 +1,143 @@
+use crate::statistics::{anderson_darling, normal_quantile};
+use anyhow::{Result, bail};
+use gage_study::data::Data;
+use std::collections::BTreeMap;
+
+/// Residuals of the two-way part × operator model with interaction, whose fitted
+/// values are the cell means. Vectors are in the order of the data, which is the
+/// run order.
+pub struct Residuals {
+    pub fitted: Vec<f64>,
+    pub residuals: Vec<f64>,
+    /// Residuals divided by their estimated standard deviation
+    pub studentized: Vec<f64>,
+    /// Expected standard normal score of each residual's rank (Blom)
+    pub normal_scores: Vec<f64>,
+    /// Anderson-Darling normality statistic A² of the residuals
+    pub anderson_darling: f64,
+    pub p_value: f64,
+}
+
+impl Residuals {
+    pub fn from_data(data: &[Data]) -> Result<Self> {
+        let mut cells = BTreeMap::<(&str, &str), Vec<usize>>::new();
+        for (i, d) in data.iter().enumerate() {
+            cells
+                .entry((d.part.as_str(), d.operator.as_str()))
+                .or_default()
+                .push(i);
+        }
+        let dof = data.len().saturating_sub(cells.len());
+        if dof == 0 {
+            bail!("Residuals require repeated readings of a part by an operator");
+        }
+
+        let mut fitted = vec![0.0; data.len()];
+        let mut leverage = vec![0.0; data.len()];
+        for rows in cells.values() {
+            let mean = rows.iter().map(|i| data[*i].measured).sum::<f64>() / rows.len() as f64;
+            for i in rows.iter() {
+                fitted[*i] = mean;
+                leverage[*i] = 1.0 / rows.len() as f64;
+            }
+        }
+        let residuals = data
+            .iter()
+            .zip(fitted.iter())
+            .map(|(d, fit)| d.measured - fit)
+            .collect::<Vec<f64>>();
+        let mse = residuals.iter().map(|r| r.powi(2)).sum::<f64>() / dof as f64;
+        let studentized = residuals
+            .iter()
+            .zip(leverage.iter())
+            .map(|(r, h)| {
+                let scale = (mse * (1.0 - h)).sqrt();
+                if scale > 0.0 { r / scale } else { 0.0 }
+            })
+            .collect::<Vec<f64>>();
+
+        let mut order = (0..residuals.len()).collect::<Vec<usize>>();
+        order.sort_by(|a, b| residuals[*a].total_cmp(&residuals[*b]));
+        let n = residuals.len() as f64;
+        let mut normal_scores = vec![0.0; residuals.len()];
+        for (rank, i) in order.into_iter().enumerate() {
+            normal_scores[i] = normal_quantile((rank as f64 + 1.0 - 0.375) / (n + 0.25));
+        }
+        let (anderson_darling, p_value) = anderson_darling(&residuals);
+
+        Ok(Self {
+            fitted,
+            residuals,
+            studentized,
+            normal_scores,
+            anderson_darling,
+            p_value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::make_data;
+
+    fn crossed() -> Vec<Data> {
+        [
+            ("P1", "A", [1.0, 3.0]),
+            ("P1", "B", [2.0, 4.0]),
+            ("P2", "A", [5.0, 7.0]),
+            ("P2", "B", [10.0, 12.0]),
+        ]
+        .into_iter()
+        .flat_map(|(part, operator, values)| {
+            values
+                .into_iter()
+                .enumerate()
+                .map(move |(replicate, measured)| {
+                    make_data("Study", part, operator, replicate + 1, measured, 0.0).unwrap()
+                })
+        })
+        .collect()
+    }
+
+    #[test]
+    fn fitted_values_are_cell_means() {
+        let residuals = Residuals::from_data(&crossed()).unwrap();
+        assert_eq!(
+            residuals.fitted,
+            vec![2.0, 2.0, 3.0, 3.0, 6.0, 6.0, 11.0, 11.0]
+        );
+        assert_eq!(
+            residuals.residuals,
+            vec![-1.0, 1.0, -1.0, 1.0, -1.0, 1.0, -1.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn studentized_by_leverage() {
+        // MSE = 8 / 4 = 2 and each reading has leverage 1/2, so the scale is 1
+        let residuals = Residuals::from_data(&crossed()).unwrap();
+        for (r, s) in residuals.residuals.iter().zip(residuals.studentized.iter()) {
+            assert!((r - s).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn normal_scores_follow_rank() {
+        let residuals = Residuals::from_data(&crossed()).unwrap();
+        // Blom scores of tied ranks are still distinct and symmetric about zero
+        assert!(residuals.normal_scores.iter().sum::<f64>().abs() < 1e-12);
+        let lowest = residuals
+            .normal_scores
+            .iter()
+            .copied()
+            .fold(f64::INFINITY, f64::min);
+        assert!((lowest - normal_quantile(0.625 / 8.25)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn single_readings_are_rejected() {
+        let data = crossed().into_iter().step_by(2).collect::<Vec<Data>>();
+        assert!(Residuals::from_data(&data).is_err());
+    }
+}
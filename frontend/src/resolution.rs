@@ -0,0 +1,282 @@
+use crate::statistics::d4;
+use anyhow::{Result, bail};
+use gage_study::data::Data;
+use std::collections::BTreeMap;
+
+/// Readings are treated as exact multiples of the resolution within this
+/// fraction of it, to absorb floating point noise from parsing
+const INCREMENT_TOLERANCE: f64 = 1e-6;
+
+/// Gage resolution and discrimination: whether the gauge reads finely enough to
+/// see the variation the study is meant to measure.
+pub struct ResolutionCheck {
+    pub resolution: f64,
+    /// Inferred from the smallest increment in the readings rather than entered
+    pub inferred: bool,
+    /// Distinct measured values for each part, across operators and replicates
+    pub distinct_per_part: Vec<(String, usize)>,
+    /// Upper control limit of the part-by-operator ranges
+    pub ucl_range: f64,
+    /// Possible range values, as multiples of the resolution, from zero up to UCL_R
+    pub range_categories: usize,
+    /// Fraction of part-by-operator ranges that are zero
+    pub zero_ranges: f64,
+}
+
+impl ResolutionCheck {
+    /// `resolution` is the gauge's known resolution; when `None` it is inferred
+    /// from the smallest increment present in `measured`
+    pub fn from_data(data: &[Data], resolution: Option<f64>) -> Result<Self> {
+        let measured = data.iter().map(|d| d.measured).collect::<Vec<f64>>();
+        let (resolution, inferred) = match resolution.filter(|r| *r > 0.0) {
+            Some(resolution) => (resolution, false),
+            None => match Self::infer_resolution(&measured) {
+                Some(resolution) => (resolution, true),
+                None => {
+                    bail!("Resolution cannot be inferred from fewer than two distinct readings")
+                }
+            },
+        };
+
+        let mut parts = BTreeMap::<&str, Vec<f64>>::new();
+        let mut cells = BTreeMap::<(&str, &str), Vec<f64>>::new();
+        for d in data.iter() {
+            parts.entry(d.part.as_str()).or_default().push(d.measured);
+            cells
+                .entry((d.part.as_str(), d.operator.as_str()))
+                .or_default()
+                .push(d.measured);
+        }
+        let distinct_per_part = parts
+            .into_iter()
+            .map(|(part, values)| (part.to_owned(), distinct_count(&values, resolution)))
+            .collect();
+
+        let ranges = cells
+            .values()
+            .filter(|values| values.len() > 1)
+            .map(|values| {
+                let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+                max - min
+            })
+            .collect::<Vec<f64>>();
+        if ranges.is_empty() {
+            bail!("Discrimination check requires repeated readings of a part by an operator");
+        }
+        let replicates = cells.values().map(Vec::len).min().unwrap_or(2);
+        let average_range = ranges.iter().sum::<f64>() / ranges.len() as f64;
+        let ucl_range = d4(replicates) * average_range;
+        let range_categories = (ucl_range / resolution + INCREMENT_TOLERANCE).floor() as usize + 1;
+        let zero_ranges =
+            ranges.iter().filter(|r| **r < resolution * 0.5).count() as f64 / ranges.len() as f64;
+
+        Ok(Self {
+            resolution,
+            inferred,
+            distinct_per_part,
+            ucl_range,
+            range_categories,
+            zero_ranges,
+        })
+    }
+
+    /// Smallest increment between the readings, rounded to the decimal places
+    /// they are recorded with
+    pub fn infer_resolution(values: &[f64]) -> Option<f64> {
+        let mut values = values
+            .iter()
+            .copied()
+            .filter(|v| v.is_finite())
+            .collect::<Vec<f64>>();
+        values.sort_by(f64::total_cmp);
+        let decimals = (0..=9)
+            .find(|d| {
+                let scale = 10f64.powi(*d);
+                values
+                    .iter()
+                    .all(|v| ((v * scale).round() - v * scale).abs() < INCREMENT_TOLERANCE * scale)
+            })
+            .unwrap_or(9);
+        let scale = 10f64.powi(decimals);
+        values
+            .windows(2)
+            .map(|w| ((w[1] - w[0]) * scale).round() / scale)
+            .filter(|step| *step > 0.0)
+            .min_by(f64::total_cmp)
+    }
+
+    /// AIAG range chart test: too few possible range values below UCL_R means the
+    /// gauge cannot discriminate between parts
+    pub fn inadequate_discrimination(&self) -> bool {
+        self.range_categories <= 3 || (self.range_categories == 4 && self.zero_ranges > 0.25)
+    }
+
+    /// Fewest distinct values measured on any part
+    pub fn min_distinct(&self) -> Option<usize> {
+        self.distinct_per_part.iter().map(|(_, n)| *n).min()
+    }
+
+    /// Warnings for a gauge too coarse for the tolerance, the process variation
+    /// (`process_spread`, in the same units) or the part-to-part discrimination
+    pub fn warnings(&self, tolerance: f64, process_spread: f64) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if tolerance > 0.0 && self.resolution > tolerance / 10.0 {
+            warnings.push(format!(
+                "Resolution {} exceeds 1/10 of the tolerance ({:.4})",
+                self.resolution,
+                tolerance / 10.0
+            ));
+        }
+        if process_spread > 0.0 && self.resolution > process_spread / 10.0 {
+            warnings.push(format!(
+                "Resolution {} exceeds 1/10 of the process variation ({:.4})",
+                self.resolution,
+                process_spread / 10.0
+            ));
+        }
+        if self.inadequate_discrimination() {
+            warnings.push(format!(
+                "Inadequate discrimination: {} possible range values within UCL_R, {:.0}% of ranges zero",
+                self.range_categories,
+                self.zero_ranges * 100.0
+            ));
+        }
+        let flat = self
+            .distinct_per_part
+            .iter()
+            .filter(|(_, n)| *n < 2)
+            .map(|(part, _)| part.as_str())
+            .collect::<Vec<&str>>();
+        if !flat.is_empty() {
+            warnings.push(format!("Every reading identical for: {}", flat.join(", ")));
+        }
+        warnings
+    }
+}
+
+fn distinct_count(values: &[f64], resolution: f64) -> usize {
+    let mut steps = values
+        .iter()
+        .map(|v| (v / resolution).round() as i64)
+        .collect::<Vec<i64>>();
+    steps.sort();
+    steps.dedup();
+    steps.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::make_data;
+
+    /// Two parts crossed with two operators, one pair of replicates per cell
+    fn readings(cells: [[f64; 2]; 4]) -> Vec<Data> {
+        let names = [("1", "A"), ("1", "B"), ("2", "A"), ("2", "B")];
+        names
+            .iter()
+            .zip(cells.iter())
+            .flat_map(|((part, operator), values)| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| make_data("", part, operator, i + 1, *v, 0.0).unwrap())
+            })
+            .collect()
+    }
+
+    fn check(range_categories: usize, zero_ranges: f64) -> ResolutionCheck {
+        ResolutionCheck {
+            resolution: 0.01,
+            inferred: false,
+            distinct_per_part: vec![("1".to_owned(), 3), ("2".to_owned(), 3)],
+            ucl_range: 0.05,
+            range_categories,
+            zero_ranges,
+        }
+    }
+
+    #[test]
+    fn resolution_is_the_smallest_increment() {
+        let fine = [1.002, 1.005, 1.003, 1.010];
+        assert_eq!(ResolutionCheck::infer_resolution(&fine), Some(0.001));
+        let coarse = [2.005, 2.010, 2.020, 2.035, 2.010];
+        assert_eq!(ResolutionCheck::infer_resolution(&coarse), Some(0.005));
+    }
+
+    #[test]
+    fn floating_point_noise_is_absorbed() {
+        // 0.1 + 0.2 parses to 0.30000000000000004
+        let values = [0.1 + 0.2, 0.7 - 0.2, 0.1 * 9.0, 0.4];
+        assert_eq!(ResolutionCheck::infer_resolution(&values), Some(0.1));
+        let noisy = (0..5)
+            .map(|i| 1.0 + i as f64 * 0.002 + 1e-12)
+            .collect::<Vec<f64>>();
+        assert_eq!(ResolutionCheck::infer_resolution(&noisy), Some(0.002));
+    }
+
+    #[test]
+    fn identical_readings_have_no_resolution() {
+        assert_eq!(ResolutionCheck::infer_resolution(&[3.2; 4]), None);
+        assert_eq!(ResolutionCheck::infer_resolution(&[]), None);
+        let data = readings([[3.2; 2]; 4]);
+        assert!(ResolutionCheck::from_data(&data, None).is_err());
+        // With a known resolution every range is zero and each part reads flat
+        let check = ResolutionCheck::from_data(&data, Some(0.01)).unwrap();
+        assert_eq!(check.range_categories, 1);
+        assert_eq!(check.zero_ranges, 1.0);
+        assert_eq!(check.min_distinct(), Some(1));
+        assert!(check.inadequate_discrimination());
+        let warnings = check.warnings(0.0, 0.0);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[1].ends_with("1, 2"));
+    }
+
+    #[test]
+    fn range_categories_count_the_upper_limit() {
+        // Every range is 0.02, so UCL_R = 3.267 x 0.02; a resolution of a third
+        // of it puts the limit exactly on the third increment
+        let data = readings([[1.0, 1.02], [2.0, 2.02], [3.0, 3.02], [4.0, 4.02]]);
+        let resolution = d4(2) * 0.02 / 3.0;
+        let check = ResolutionCheck::from_data(&data, Some(resolution)).unwrap();
+        assert!(!check.inferred);
+        assert!((check.ucl_range - 0.06534).abs() < 1e-9);
+        assert_eq!(check.range_categories, 4);
+        assert_eq!(check.zero_ranges, 0.0);
+        assert!(!check.inadequate_discrimination());
+        // Inferred from the readings, 0.02 steps give 3.267 increments
+        let check = ResolutionCheck::from_data(&data, None).unwrap();
+        assert!(check.inferred);
+        assert_eq!(check.resolution, 0.02);
+        assert_eq!(check.range_categories, 4);
+        assert_eq!(
+            check.distinct_per_part,
+            vec![("1".to_owned(), 4), ("2".to_owned(), 4)]
+        );
+    }
+
+    #[test]
+    fn discrimination_threshold() {
+        assert!(check(3, 0.0).inadequate_discrimination());
+        assert!(!check(4, 0.25).inadequate_discrimination());
+        assert!(check(4, 0.5).inadequate_discrimination());
+        assert!(!check(5, 1.0).inadequate_discrimination());
+    }
+
+    #[test]
+    fn warnings_compare_the_resolution_to_a_tenth() {
+        // 0.01 is above a tenth of a 0.05 tolerance but equal to a tenth of 0.1
+        let warnings = check(5, 0.0).warnings(0.05, 0.1);
+        assert_eq!(
+            warnings,
+            vec!["Resolution 0.01 exceeds 1/10 of the tolerance (0.0050)".to_owned()]
+        );
+        assert!(check(5, 0.0).warnings(0.1, 1.0).is_empty());
+        // Zero tolerance and spread are treated as unknown
+        let warnings = check(3, 0.5).warnings(0.0, 0.0);
+        assert_eq!(
+            warnings,
+            vec!["Inadequate discrimination: 3 possible range values within UCL_R, 50% of ranges zero".to_owned()]
+        );
+    }
+}
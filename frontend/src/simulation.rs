@@ -0,0 +1,159 @@
+use crate::{Design, VarianceComponents, make_data, study_plan::Interval};
+use anyhow::{Result, bail};
+use gage_study::{anova::Anova, data::Data, dataset::DataSet, study_evaluation::StudyEvaluation};
+use rand::{SeedableRng, rngs::StdRng};
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+
+/// True standard deviations of the simulated measurement system
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Sigmas {
+    pub part: f64,
+    pub operator: f64,
+    pub interaction: f64,
+    pub repeatability: f64,
+}
+
+impl Sigmas {
+    pub fn gagerr(&self) -> f64 {
+        (self.operator.powi(2) + self.interaction.powi(2) + self.repeatability.powi(2)).sqrt()
+    }
+
+    pub fn percent_study_var(&self) -> f64 {
+        let gagerr = self.gagerr();
+        gagerr / (gagerr.powi(2) + self.part.powi(2)).sqrt() * 100.0
+    }
+
+    pub fn ndc(&self) -> f64 {
+        1.41 * self.part / self.gagerr()
+    }
+}
+
+/// Sampling distribution of %Study Var and ndc, from synthetic crossed studies
+/// analysed exactly as loaded data is: `Anova::from_data`, `StudyEvaluation` and
+/// `VarianceComponents`. The true values follow from the sigmas; readings rounded
+/// to a coarse resolution bias the estimates away from them.
+pub struct Simulation {
+    pub sigmas: Sigmas,
+    pub design: Design,
+    /// Gage resolution readings are rounded to, zero for none
+    pub resolution: f64,
+    pub true_percent_study_var: f64,
+    pub true_ndc: f64,
+    pub percent_study_var: Vec<f64>,
+    pub ndc: Vec<f64>,
+    /// Runs in which the study found no gage variation, leaving ndc undefined
+    pub undefined_ndc: usize,
+}
+
+impl Simulation {
+    pub fn from_sigmas(
+        sigmas: Sigmas,
+        design: Design,
+        resolution: f64,
+        runs: usize,
+        seed: u64,
+    ) -> Result<Self> {
+        if sigmas.part <= 0.0
+            || sigmas.operator < 0.0
+            || sigmas.interaction < 0.0
+            || sigmas.repeatability <= 0.0
+        {
+            bail!("Part and repeatability σ must be positive, the others non-negative");
+        }
+        if resolution < 0.0 {
+            bail!("Resolution must not be negative");
+        }
+        if design.parts < 2 || design.operators < 2 || design.replicates < 2 {
+            bail!(
+                "Design {} requires at least two parts, operators and replicates",
+                design.label()
+            );
+        }
+        if runs < 10 {
+            bail!("Simulation requires at least 10 runs");
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut percent_study_var = Vec::with_capacity(runs);
+        let mut ndc = Vec::with_capacity(runs);
+        let mut undefined_ndc = 0;
+        for _ in 0..runs {
+            let data = synthetic_data(sigmas, design, resolution, &mut rng)?;
+            let dataset = DataSet::from_data("simulation", &data);
+            let anova = Anova::from_data(&dataset);
+            let study = StudyEvaluation::from_anova(&anova);
+            let components = VarianceComponents::from_study_evaluation(&study, &anova, &data);
+            let gagerr = components.total_gagerr.stddev;
+            percent_study_var.push(gagerr / components.total_variation.stddev * 100.0);
+            if gagerr > 0.0 {
+                ndc.push(1.41 * components.part_to_part.stddev / gagerr);
+            } else {
+                undefined_ndc += 1;
+            }
+        }
+        Ok(Self {
+            sigmas,
+            design,
+            resolution,
+            true_percent_study_var: sigmas.percent_study_var(),
+            true_ndc: sigmas.ndc(),
+            percent_study_var,
+            ndc,
+            undefined_ndc,
+        })
+    }
+
+    pub fn percent_study_var_interval(&self) -> Interval {
+        Interval::from_samples(self.percent_study_var.clone())
+    }
+
+    /// `None` when no run produced a defined ndc
+    pub fn ndc_interval(&self) -> Option<Interval> {
+        (!self.ndc.is_empty()).then(|| Interval::from_samples(self.ndc.clone()))
+    }
+}
+
+/// One synthetic crossed study with mean zero: part, operator and interaction
+/// effects plus repeatability error, rounded to `resolution`
+fn synthetic_data(
+    sigmas: Sigmas,
+    design: Design,
+    resolution: f64,
+    rng: &mut StdRng,
+) -> Result<Vec<Data>> {
+    let standard = Normal::new(0.0, 1.0)?;
+    let part_ids = design.part_ids();
+    let operator_ids = design.operator_ids();
+    let part_effects = part_ids
+        .iter()
+        .map(|_| sigmas.part * standard.sample(rng))
+        .collect::<Vec<f64>>();
+    let operator_effects = operator_ids
+        .iter()
+        .map(|_| sigmas.operator * standard.sample(rng))
+        .collect::<Vec<f64>>();
+    let mut data = Vec::with_capacity(design.runs());
+    for (operator, operator_effect) in operator_ids.iter().zip(operator_effects.iter()) {
+        for (part, part_effect) in part_ids.iter().zip(part_effects.iter()) {
+            let interaction = sigmas.interaction * standard.sample(rng);
+            for replicate in 1..=design.replicates {
+                let mut measured = part_effect
+                    + operator_effect
+                    + interaction
+                    + sigmas.repeatability * standard.sample(rng);
+                if resolution > 0.0 {
+                    measured = (measured / resolution).round() * resolution;
+                }
+                data.push(make_data(
+                    &format!("{part} {operator} {replicate}"),
+                    part,
+                    operator,
+                    replicate,
+                    measured,
+                    0.0,
+                )?);
+            }
+        }
+    }
+    Ok(data)
+}
@@ -0,0 +1,191 @@
+use crate::{Design, Sigmas, Simulation};
+use eframe::egui;
+use egui_plot::{Bar, BarChart, Legend, LineStyle, Plot, VLine};
+use serde::{Deserialize, Serialize};
+
+/// Monte Carlo gage R&R simulator: distributions of %Study Var and ndc for a
+/// measurement system with known variance components.
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct Simulator {
+    pub sigmas: Sigmas,
+    pub design: Design,
+    pub resolution: f64,
+    pub runs: usize,
+    pub seed: u64,
+    #[serde(skip)]
+    simulation: Option<Simulation>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Self {
+            sigmas: Sigmas {
+                part: 1.0,
+                operator: 0.1,
+                interaction: 0.05,
+                repeatability: 0.15,
+            },
+            design: Design::new(10, 3, 2),
+            resolution: 0.0,
+            runs: 500,
+            seed: 1,
+            simulation: None,
+            error: None,
+        }
+    }
+}
+
+impl Simulator {
+    pub fn name(&self) -> &'static str {
+        "☰ Gage R&R Simulator"
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(700.0)
+            .show(ctx, |ui| {
+                self.ui(ui);
+            });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.columns(2, |columns| {
+            egui::Grid::new("simulator_sigmas").show(&mut columns[0], |ui| {
+                for (label, value) in [
+                    ("Part σ: ", &mut self.sigmas.part),
+                    ("Operator σ: ", &mut self.sigmas.operator),
+                    ("Part × Operator σ: ", &mut self.sigmas.interaction),
+                    ("Repeatability σ: ", &mut self.sigmas.repeatability),
+                    ("Resolution: ", &mut self.resolution),
+                ] {
+                    ui.label(label);
+                    ui.add(
+                        egui::DragValue::new(value)
+                            .speed(0.01)
+                            .range(0.0..=f64::MAX),
+                    );
+                    ui.end_row();
+                }
+            });
+            egui::Grid::new("simulator_design").show(&mut columns[1], |ui| {
+                ui.label("Parts: ");
+                ui.add(egui::DragValue::new(&mut self.design.parts).range(2..=100));
+                ui.end_row();
+                ui.label("Operators: ");
+                ui.add(egui::DragValue::new(&mut self.design.operators).range(2..=26));
+                ui.end_row();
+                ui.label("Replicates: ");
+                ui.add(egui::DragValue::new(&mut self.design.replicates).range(2..=20));
+                ui.end_row();
+                ui.label("Runs: ");
+                ui.add(
+                    egui::DragValue::new(&mut self.runs)
+                        .speed(10)
+                        .range(10..=10000),
+                );
+                ui.end_row();
+                ui.label("Seed: ");
+                ui.add(egui::DragValue::new(&mut self.seed));
+                ui.end_row();
+            });
+        });
+        if ui.button("Simulate").clicked() {
+            self.simulate();
+        }
+        if let Some(err) = &self.error {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+        let Some(simulation) = &self.simulation else {
+            return;
+        };
+        ui.separator();
+        ui.style_mut().override_text_style = Some(egui::style::TextStyle::Monospace);
+        let interval = simulation.percent_study_var_interval();
+        ui.label(format!(
+            "%Study Var  true {:>6.2}  median {:>6.2}  95% range {:>6.2} - {:>6.2}",
+            simulation.true_percent_study_var, interval.median, interval.lower, interval.upper
+        ));
+        match simulation.ndc_interval() {
+            Some(interval) => ui.label(format!(
+                "ndc        true {:>6.2}  median {:>6.2}  95% range {:>6.2} - {:>6.2}",
+                simulation.true_ndc, interval.median, interval.lower, interval.upper
+            )),
+            None => ui.label("ndc        undefined in every run"),
+        };
+        if simulation.undefined_ndc > 0 {
+            ui.label(format!(
+                "{} runs estimated no gage variation (ndc undefined)",
+                simulation.undefined_ndc
+            ));
+        }
+        ui.columns(2, |columns| {
+            histogram(
+                &mut columns[0],
+                "%Study Var",
+                &simulation.percent_study_var,
+                simulation.true_percent_study_var,
+            );
+            histogram(&mut columns[1], "ndc", &simulation.ndc, simulation.true_ndc);
+        });
+    }
+
+    fn simulate(&mut self) {
+        match Simulation::from_sigmas(
+            self.sigmas,
+            self.design,
+            self.resolution,
+            self.runs,
+            self.seed,
+        ) {
+            Ok(simulation) => {
+                self.simulation = Some(simulation);
+                self.error = None;
+            }
+            Err(e) => {
+                tracing::error!("Simulation::from_sigmas: {e:?}");
+                self.simulation = None;
+                self.error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+/// Histogram of simulated estimates with the true value marked
+fn histogram(ui: &mut egui::Ui, name: &str, values: &[f64], true_value: f64) {
+    const BINS: usize = 30;
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if values.is_empty() || !min.is_finite() || !max.is_finite() {
+        return;
+    }
+    let width = ((max - min) / BINS as f64).max(f64::EPSILON);
+    let mut counts = [0usize; BINS];
+    for value in values.iter() {
+        let bin = (((value - min) / width) as usize).min(BINS - 1);
+        counts[bin] += 1;
+    }
+    let bars = counts
+        .iter()
+        .enumerate()
+        .map(|(i, count)| Bar::new(min + (i as f64 + 0.5) * width, *count as f64).width(width))
+        .collect::<Vec<Bar>>();
+    Plot::new(("simulation_histogram", name))
+        .legend(Legend::default())
+        .height(250.0)
+        .x_axis_label(name)
+        .y_axis_label("Runs")
+        .set_margin_fraction(egui::Vec2 { x: 0.1, y: 0.1 })
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(BarChart::new(name, bars).color(egui::Color32::LIGHT_BLUE));
+            plot_ui.vline(
+                VLine::new("true value", true_value)
+                    .color(egui::Color32::RED)
+                    .style(LineStyle::dashed_loose()),
+            );
+        });
+}
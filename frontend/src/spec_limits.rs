@@ -0,0 +1,62 @@
+use gage_study::data::Data;
+
+/// Specification of the measured characteristic, drawn over the part
+/// measurements to show the spread of the readings against the tolerance
+#[derive(Clone, Default)]
+pub struct SpecLimits {
+    pub nominal: Option<f64>,
+    pub lsl: Option<f64>,
+    pub usl: Option<f64>,
+    /// Units of the readings for axis labels, none when empty
+    pub units: String,
+}
+
+impl SpecLimits {
+    /// Nominal shared by all readings; readings without one carry zero, which is
+    /// taken as not recorded
+    pub fn from_data(data: &[Data]) -> Self {
+        let nominal = data
+            .first()
+            .map(|d| d.nominal)
+            .filter(|nominal| *nominal != 0.0 && data.iter().all(|d| d.nominal == *nominal));
+        Self {
+            nominal,
+            ..Default::default()
+        }
+    }
+
+    /// Limits entered in the study settings, taking the nominal midway between
+    /// them when the readings do not give one
+    pub fn with_limits(mut self, lsl: f64, usl: f64) -> Self {
+        if lsl < usl {
+            self.lsl = Some(lsl);
+            self.usl = Some(usl);
+            self.nominal = self.nominal.or(Some((lsl + usl) / 2.0));
+        }
+        self
+    }
+
+    /// Limits centred on the nominal, where none were entered
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        let nominal = self.nominal.filter(|_| tolerance > 0.0);
+        if let (Some(nominal), None, None) = (nominal, self.lsl, self.usl) {
+            self.lsl = Some(nominal - tolerance / 2.0);
+            self.usl = Some(nominal + tolerance / 2.0);
+        }
+        self
+    }
+
+    pub fn with_units(mut self, units: &str) -> Self {
+        self.units = units.trim().to_owned();
+        self
+    }
+
+    /// `label` followed by the units in parentheses, when known
+    pub fn axis_label(&self, label: &str) -> String {
+        if self.units.is_empty() {
+            label.to_owned()
+        } else {
+            format!("{label} ({})", self.units)
+        }
+    }
+}
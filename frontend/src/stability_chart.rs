@@ -0,0 +1,134 @@
+use crate::{ChartType, ControlChart, ExportSettings, Figure, Mark, Marker, StabilityStudy};
+use eframe::egui;
+
+#[derive(Default)]
+pub struct StabilityChart<'a> {
+    pub study: Option<&'a StabilityStudy>,
+}
+
+impl<'a> StabilityChart<'a> {
+    pub fn name(&self) -> &'static str {
+        "☰ Stability Control Charts"
+    }
+
+    /// Shows the charts, returning the file name and content of an exported image
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        study: &'a Option<StabilityStudy>,
+        open: &mut bool,
+    ) -> Option<(String, Vec<u8>)> {
+        self.study = study.as_ref();
+        let mut export = None;
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(400.0)
+            .default_height(500.0)
+            .show(ctx, |ui| {
+                export = self.ui(ui);
+            });
+        export
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<(String, Vec<u8>)> {
+        let study = self.study?;
+        let chart_height = (ui.available_height() - ui.spacing().item_spacing.y) / 2.0;
+        let mut export = None;
+        for chart in [&study.location, &study.dispersion] {
+            let id = format!("stability_chart_{}", chart.name);
+            let figure = Self::control_chart(study, chart);
+            let top = ui.cursor().top();
+            if let Some(image) = ExportSettings::ui(ui, &id, &figure) {
+                export = Some(image);
+            }
+            // The export settings take their share of the chart's height
+            let height = chart_height - (ui.cursor().top() - top);
+            figure.with_height(height).show(ui, &id, &[], &[]);
+        }
+        export
+    }
+
+    fn control_chart(study: &StabilityStudy, chart: &ControlChart) -> Figure {
+        let positions = study.chart_positions(chart);
+        let points = positions
+            .iter()
+            .zip(chart.values.iter())
+            .map(|(x, y)| [*x, *y])
+            .collect::<Vec<[f64; 2]>>();
+        let flagged = points
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| chart.is_flagged(*i))
+            .map(|(_, p)| *p)
+            .collect::<Vec<[f64; 2]>>();
+        let x_label = match (study.has_dates(), &study.chart_type) {
+            (true, _) => "Days",
+            (false, ChartType::XbarS) => "Subgroup",
+            (false, _) => "Observation",
+        };
+        // Tooltips give the timestamp of the nearest point
+        let x_labels = positions
+            .iter()
+            .copied()
+            .zip(chart.labels.iter().cloned())
+            .collect();
+        let mut figure = Figure::new(chart.name, x_label, chart.name)
+            .with_x_labels(x_labels)
+            .with_linked_x("stability_chart");
+        figure.push(
+            "center",
+            egui::Color32::DARK_GREEN,
+            Mark::HLine {
+                y: chart.center,
+                width: 1.5,
+                dashed: false,
+            },
+        );
+        for y in [chart.ucl, chart.lcl] {
+            figure.push(
+                "UCL / LCL",
+                egui::Color32::RED,
+                Mark::HLine {
+                    y,
+                    width: 1.0,
+                    dashed: true,
+                },
+            );
+        }
+        figure.push(
+            chart.name,
+            egui::Color32::BLUE,
+            Mark::Line {
+                points: points.clone(),
+                highlight: false,
+                dashed: false,
+            },
+        );
+        figure.push(
+            chart.name,
+            egui::Color32::BLUE,
+            Mark::Points {
+                points,
+                marker: Marker::Circle,
+                radius: 3.0,
+                filled: true,
+                highlight: false,
+                rows: Vec::new(),
+            },
+        );
+        figure.push(
+            "rule violation",
+            egui::Color32::RED,
+            Mark::Points {
+                points: flagged,
+                marker: Marker::Square,
+                radius: 4.0,
+                filled: true,
+                highlight: false,
+                rows: Vec::new(),
+            },
+        );
+        figure
+    }
+}
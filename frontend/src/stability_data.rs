@@ -0,0 +1,107 @@
+use crate::ExpandedData;
+use anyhow::{Result, bail};
+use gage_study::data::Data;
+use serde::{Deserialize, Serialize};
+
+/// A measurement of a reference part with the time it was taken.
+/// Timestamps are ISO 8601 dates or date-times (`2024-03-01`, `2024-03-01T08:30`).
+#[derive(Clone, Deserialize, Serialize)]
+pub struct StabilityData {
+    pub data: Data,
+    pub timestamp: String,
+}
+
+impl StabilityData {
+    /// Reads the `Data` columns plus a `timestamp` column from CSV or JSON
+    pub fn from_raw(raw: &[u8], ext: &str) -> Result<Vec<Self>> {
+        ExpandedData::from_raw(raw, ext)?
+            .into_iter()
+            .map(|mut d| {
+                let Some(timestamp) = d.factors.remove("timestamp") else {
+                    bail!("Stability data requires a timestamp column");
+                };
+                Ok(Self {
+                    data: d.data,
+                    timestamp,
+                })
+            })
+            .collect()
+    }
+
+    /// Timestamp in days since 1970-01-01, if it can be parsed
+    pub fn days(&self) -> Option<f64> {
+        parse_timestamp(&self.timestamp)
+    }
+}
+
+/// Parses `YYYY-MM-DD` optionally followed by `T` or a space and `HH:MM[:SS[.fff]]`
+fn parse_timestamp(timestamp: &str) -> Option<f64> {
+    let timestamp = timestamp.trim().trim_end_matches('Z');
+    let (date, time) = match timestamp.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (timestamp, None),
+    };
+    let mut date = date.split('-');
+    let year = date.next()?.parse::<i64>().ok()?;
+    let month = date.next()?.parse::<i64>().ok()?;
+    let day = date.next()?.parse::<i64>().ok()?;
+    if date.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut seconds = 0.0;
+    if let Some(time) = time {
+        let mut time = time.split(':');
+        let hours = time.next()?.parse::<f64>().ok()?;
+        let minutes = time.next()?.parse::<f64>().ok()?;
+        let secs = time.next().map_or(Some(0.0), |s| s.parse::<f64>().ok())?;
+        seconds = hours * 3600.0 + minutes * 60.0 + secs;
+    }
+    // Days from civil date, proleptic Gregorian calendar
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    Some(days as f64 + seconds / 86400.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(parse_timestamp("1970-01-01"), Some(0.0));
+        assert_eq!(parse_timestamp("2024-03-01"), Some(19783.0));
+        assert_eq!(parse_timestamp("2000-02-29"), Some(11016.0));
+        assert_eq!(parse_timestamp(" 2024-03-01 "), Some(19783.0));
+    }
+
+    #[test]
+    fn parses_date_times() {
+        assert_eq!(parse_timestamp("2024-03-01T12:00"), Some(19783.5));
+        assert_eq!(parse_timestamp("2024-03-01 06:00:00"), Some(19783.25));
+        assert_eq!(parse_timestamp("2024-03-01T18:00:00Z"), Some(19783.75));
+        let days = parse_timestamp("1970-01-01T00:00:43.2").unwrap();
+        assert!((days - 0.0005).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rejects_other_formats() {
+        for timestamp in [
+            "",
+            "01/03/2024",
+            "2024-13-01",
+            "2024-03-32",
+            "2024-03",
+            "2024-03-01-02",
+            "2024-03-01T12",
+            "2024-03-01Tnoon",
+            "run 5",
+        ] {
+            assert_eq!(parse_timestamp(timestamp), None, "{timestamp}");
+        }
+    }
+}
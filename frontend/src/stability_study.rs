@@ -0,0 +1,608 @@
+use crate::{
+    StabilityData,
+    statistics::{c4, t_test_p_value},
+};
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ChartType {
+    /// Individuals and moving range
+    IndividualsMovingRange,
+    /// Subgroup means and standard deviations
+    XbarS,
+}
+
+impl ChartType {
+    pub const ALL: [ChartType; 2] = [ChartType::IndividualsMovingRange, ChartType::XbarS];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChartType::IndividualsMovingRange => "I-MR",
+            ChartType::XbarS => "Xbar-S",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RuleSet {
+    WesternElectric,
+    Nelson,
+}
+
+impl RuleSet {
+    pub const ALL: [RuleSet; 2] = [RuleSet::WesternElectric, RuleSet::Nelson];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RuleSet::WesternElectric => "Western Electric",
+            RuleSet::Nelson => "Nelson",
+        }
+    }
+
+    pub fn rules(&self) -> Vec<RunRule> {
+        match self {
+            RuleSet::WesternElectric => vec![
+                RunRule::BeyondLimits,
+                RunRule::TwoOfThree,
+                RunRule::FourOfFive,
+                RunRule::SameSide(8),
+            ],
+            RuleSet::Nelson => vec![
+                RunRule::BeyondLimits,
+                RunRule::SameSide(9),
+                RunRule::Trend,
+                RunRule::Alternating,
+                RunRule::TwoOfThree,
+                RunRule::FourOfFive,
+                RunRule::Stratification,
+                RunRule::Mixture,
+            ],
+        }
+    }
+}
+
+/// Run rules for detecting non-random patterns on a control chart.
+/// Zones are measured in sigma = (UCL - center) / 3.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunRule {
+    /// One point beyond the control limits
+    BeyondLimits,
+    /// n points in a row on the same side of the center line
+    SameSide(usize),
+    /// Six points in a row steadily increasing or decreasing
+    Trend,
+    /// Fourteen points in a row alternating up and down
+    Alternating,
+    /// Two of three points in a row beyond 2 sigma on the same side
+    TwoOfThree,
+    /// Four of five points in a row beyond 1 sigma on the same side
+    FourOfFive,
+    /// Fifteen points in a row within 1 sigma
+    Stratification,
+    /// Eight points in a row beyond 1 sigma on both sides
+    Mixture,
+}
+
+impl RunRule {
+    pub fn description(&self) -> String {
+        match self {
+            RunRule::BeyondLimits => "1 point beyond the control limits".to_owned(),
+            RunRule::SameSide(n) => format!("{n} points in a row on one side of the center line"),
+            RunRule::Trend => "6 points in a row increasing or decreasing".to_owned(),
+            RunRule::Alternating => "14 points in a row alternating up and down".to_owned(),
+            RunRule::TwoOfThree => "2 of 3 points beyond 2σ on one side".to_owned(),
+            RunRule::FourOfFive => "4 of 5 points beyond 1σ on one side".to_owned(),
+            RunRule::Stratification => "15 points in a row within 1σ".to_owned(),
+            RunRule::Mixture => "8 points in a row beyond 1σ on both sides".to_owned(),
+        }
+    }
+
+    /// Number of consecutive points the rule looks at
+    fn window(&self) -> usize {
+        match self {
+            RunRule::BeyondLimits => 1,
+            RunRule::SameSide(n) => *n,
+            RunRule::Trend => 6,
+            RunRule::Alternating => 14,
+            RunRule::TwoOfThree => 3,
+            RunRule::FourOfFive => 5,
+            RunRule::Stratification => 15,
+            RunRule::Mixture => 8,
+        }
+    }
+
+    /// True when the points in `window` (as z-scores from the center line) break the rule
+    fn violated(&self, window: &[f64]) -> bool {
+        let count = |f: &dyn Fn(f64) -> bool| window.iter().filter(|z| f(**z)).count();
+        match self {
+            RunRule::BeyondLimits => window.iter().all(|z| z.abs() > 3.0),
+            RunRule::SameSide(_) => {
+                window.iter().all(|z| *z > 0.0) || window.iter().all(|z| *z < 0.0)
+            }
+            RunRule::Trend => {
+                window.windows(2).all(|w| w[1] > w[0]) || window.windows(2).all(|w| w[1] < w[0])
+            }
+            RunRule::Alternating => {
+                let steps = window.windows(2).map(|w| w[1] - w[0]).collect::<Vec<f64>>();
+                steps.windows(2).all(|s| s[0] * s[1] < 0.0)
+            }
+            RunRule::TwoOfThree => count(&|z| z > 2.0) >= 2 || count(&|z| z < -2.0) >= 2,
+            RunRule::FourOfFive => count(&|z| z > 1.0) >= 4 || count(&|z| z < -1.0) >= 4,
+            RunRule::Stratification => window.iter().all(|z| z.abs() < 1.0),
+            RunRule::Mixture => {
+                window.iter().all(|z| z.abs() > 1.0)
+                    && window.iter().any(|z| *z > 0.0)
+                    && window.iter().any(|z| *z < 0.0)
+            }
+        }
+    }
+}
+
+/// A point that completes a run rule pattern
+pub struct RuleViolation {
+    pub rule: RunRule,
+    pub index: usize,
+}
+
+pub struct ControlChart {
+    pub name: &'static str,
+    /// Timestamp of each point (of the first reading for subgroups)
+    pub labels: Vec<String>,
+    pub values: Vec<f64>,
+    pub center: f64,
+    pub ucl: f64,
+    pub lcl: f64,
+    pub violations: Vec<RuleViolation>,
+}
+
+impl ControlChart {
+    fn new(
+        name: &'static str,
+        labels: Vec<String>,
+        values: Vec<f64>,
+        center: f64,
+        ucl: f64,
+        lcl: f64,
+    ) -> Self {
+        Self {
+            name,
+            labels,
+            values,
+            center,
+            ucl,
+            lcl,
+            violations: Vec::new(),
+        }
+    }
+
+    fn apply_rules(&mut self, rules: &[RunRule]) {
+        let sigma = (self.ucl - self.center) / 3.0;
+        let z = self
+            .values
+            .iter()
+            .map(|v| (v - self.center) / sigma)
+            .collect::<Vec<f64>>();
+        self.violations.clear();
+        for rule in rules.iter() {
+            let window = rule.window();
+            if sigma <= 0.0 || z.len() < window {
+                continue;
+            }
+            for (start, points) in z.windows(window).enumerate() {
+                if rule.violated(points) {
+                    self.violations.push(RuleViolation {
+                        rule: *rule,
+                        index: start + window - 1,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Points outside the (possibly asymmetric) control limits, used for dispersion charts
+    fn apply_limits(&mut self) {
+        self.violations = self
+            .values
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| **v > self.ucl || **v < self.lcl)
+            .map(|(index, _)| RuleViolation {
+                rule: RunRule::BeyondLimits,
+                index,
+            })
+            .collect();
+    }
+
+    /// True when any rule flags the point at `index`
+    pub fn is_flagged(&self, index: usize) -> bool {
+        self.violations.iter().any(|v| v.index == index)
+    }
+}
+
+/// Linear trend of the bias (measured - reference) over time
+pub struct BiasDrift {
+    /// "day" when the timestamps could be parsed, otherwise "observation"
+    pub time_unit: &'static str,
+    pub slope: f64,
+    pub intercept: f64,
+    pub slope_se: f64,
+    pub p_value: f64,
+    pub average_bias: f64,
+}
+
+/// Stability study: a reference part re-measured over time. The reference value
+/// is taken from `Data::nominal`.
+pub struct StabilityStudy {
+    pub part: String,
+    pub reference: f64,
+    pub timestamps: Vec<String>,
+    pub values: Vec<f64>,
+    pub chart_type: ChartType,
+    pub subgroup_size: usize,
+    pub rule_set: RuleSet,
+    /// Individuals or subgroup means
+    pub location: ControlChart,
+    /// Moving ranges or subgroup standard deviations
+    pub dispersion: ControlChart,
+    pub drift: BiasDrift,
+    days: Option<Vec<f64>>,
+}
+
+impl StabilityStudy {
+    pub fn from_data(data: &[StabilityData]) -> Result<Self> {
+        let Some(first) = data.first() else {
+            bail!("Stability study requires at least one measurement");
+        };
+        if data.iter().any(|d| d.data.part != first.data.part) {
+            bail!("Stability study requires a single reference part");
+        }
+        if data.iter().any(|d| d.data.nominal != first.data.nominal) {
+            bail!("Stability study requires a single reference (nominal) value");
+        }
+        if data.len() < 3 {
+            bail!("Stability study requires at least three measurements");
+        }
+        // Chronological order; unparsable timestamps sort as text
+        let mut sorted = data.iter().collect::<Vec<&StabilityData>>();
+        let parsed = sorted.iter().all(|d| d.days().is_some());
+        if parsed {
+            sorted.sort_by(|a, b| a.days().unwrap_or(0.0).total_cmp(&b.days().unwrap_or(0.0)));
+        } else {
+            sorted.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        }
+        let days = parsed.then(|| sorted.iter().filter_map(|d| d.days()).collect::<Vec<f64>>());
+        let timestamps = sorted
+            .iter()
+            .map(|d| d.timestamp.clone())
+            .collect::<Vec<String>>();
+        let values = sorted.iter().map(|d| d.data.measured).collect::<Vec<f64>>();
+        let reference = first.data.nominal;
+        let drift = Self::bias_drift(&values, reference, days.as_deref());
+        let mut study = Self {
+            part: first.data.part.clone(),
+            reference,
+            timestamps,
+            values,
+            chart_type: ChartType::IndividualsMovingRange,
+            subgroup_size: 1,
+            rule_set: RuleSet::Nelson,
+            location: ControlChart::new("Individuals", Vec::new(), Vec::new(), 0.0, 0.0, 0.0),
+            dispersion: ControlChart::new("Moving Range", Vec::new(), Vec::new(), 0.0, 0.0, 0.0),
+            drift,
+            days,
+        };
+        study.evaluate();
+        Ok(study)
+    }
+
+    /// Xbar-S charts use consecutive subgroups of `subgroup_size` readings;
+    /// an incomplete last subgroup is left out.
+    pub fn with_chart(mut self, chart_type: ChartType, subgroup_size: usize) -> Result<Self> {
+        if chart_type == ChartType::XbarS {
+            if subgroup_size < 2 {
+                bail!("Xbar-S chart requires subgroups of at least two readings");
+            }
+            if self.values.len() / subgroup_size < 2 {
+                bail!("Xbar-S chart requires at least two complete subgroups");
+            }
+        }
+        self.chart_type = chart_type;
+        self.subgroup_size = subgroup_size;
+        self.evaluate();
+        Ok(self)
+    }
+
+    pub fn with_rule_set(mut self, rule_set: RuleSet) -> Self {
+        self.rule_set = rule_set;
+        self.evaluate();
+        self
+    }
+
+    /// Position of each chart point on the time axis: days since the first
+    /// reading when timestamps parse, otherwise the point number
+    pub fn chart_positions(&self, chart: &ControlChart) -> Vec<f64> {
+        let step = match self.chart_type {
+            ChartType::IndividualsMovingRange => 1,
+            ChartType::XbarS => self.subgroup_size,
+        };
+        // Moving ranges start at the second reading
+        let offset = self.values.len() / step - chart.values.len();
+        (0..chart.values.len())
+            .map(|i| match &self.days {
+                Some(days) => days[(i + offset) * step] - days[0],
+                None => (i + offset + 1) as f64,
+            })
+            .collect()
+    }
+
+    pub fn has_dates(&self) -> bool {
+        self.days.is_some()
+    }
+
+    fn evaluate(&mut self) {
+        let n = self.values.len() as f64;
+        let rules = self.rule_set.rules();
+        match self.chart_type {
+            ChartType::IndividualsMovingRange => {
+                let mean = self.values.iter().sum::<f64>() / n;
+                let ranges = self
+                    .values
+                    .windows(2)
+                    .map(|w| (w[1] - w[0]).abs())
+                    .collect::<Vec<f64>>();
+                let mr_bar = ranges.iter().sum::<f64>() / ranges.len() as f64;
+                // 2.66 = 3 / d2 and 3.267 = D4 for moving ranges of two
+                self.location = ControlChart::new(
+                    "Individuals",
+                    self.timestamps.clone(),
+                    self.values.clone(),
+                    mean,
+                    mean + 2.66 * mr_bar,
+                    mean - 2.66 * mr_bar,
+                );
+                self.dispersion = ControlChart::new(
+                    "Moving Range",
+                    self.timestamps[1..].to_vec(),
+                    ranges,
+                    mr_bar,
+                    3.267 * mr_bar,
+                    0.0,
+                );
+            }
+            ChartType::XbarS => {
+                let size = self.subgroup_size;
+                let subgroups = self.values.chunks_exact(size);
+                let means = subgroups
+                    .clone()
+                    .map(|s| s.iter().sum::<f64>() / size as f64)
+                    .collect::<Vec<f64>>();
+                let stddevs = subgroups
+                    .zip(means.iter())
+                    .map(|(s, m)| {
+                        (s.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (size - 1) as f64).sqrt()
+                    })
+                    .collect::<Vec<f64>>();
+                let labels = self
+                    .timestamps
+                    .chunks_exact(size)
+                    .map(|s| s[0].clone())
+                    .collect::<Vec<String>>();
+                let grand_mean = means.iter().sum::<f64>() / means.len() as f64;
+                let s_bar = stddevs.iter().sum::<f64>() / stddevs.len() as f64;
+                let c4 = c4(size);
+                let a3 = 3.0 / (c4 * (size as f64).sqrt());
+                let b = 3.0 * (1.0 - c4 * c4).sqrt() / c4;
+                self.location = ControlChart::new(
+                    "Xbar",
+                    labels.clone(),
+                    means,
+                    grand_mean,
+                    grand_mean + a3 * s_bar,
+                    grand_mean - a3 * s_bar,
+                );
+                self.dispersion = ControlChart::new(
+                    "S",
+                    labels,
+                    stddevs,
+                    s_bar,
+                    (1.0 + b) * s_bar,
+                    (1.0 - b).max(0.0) * s_bar,
+                );
+            }
+        }
+        self.location.apply_rules(&rules);
+        self.dispersion.apply_limits();
+    }
+
+    fn bias_drift(values: &[f64], reference: f64, days: Option<&[f64]>) -> BiasDrift {
+        let x = match days {
+            Some(days) => days.iter().map(|d| d - days[0]).collect::<Vec<f64>>(),
+            None => (1..=values.len()).map(|i| i as f64).collect::<Vec<f64>>(),
+        };
+        let y = values.iter().map(|v| v - reference).collect::<Vec<f64>>();
+        let n = y.len() as f64;
+        let x_mean = x.iter().sum::<f64>() / n;
+        let y_mean = y.iter().sum::<f64>() / n;
+        let sxx = x.iter().map(|v| (v - x_mean).powi(2)).sum::<f64>();
+        let sxy = x
+            .iter()
+            .zip(y.iter())
+            .map(|(a, b)| (a - x_mean) * (b - y_mean))
+            .sum::<f64>();
+        let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+        let intercept = y_mean - slope * x_mean;
+        let residual = x
+            .iter()
+            .zip(y.iter())
+            .map(|(a, b)| (b - intercept - slope * a).powi(2))
+            .sum::<f64>();
+        let s = (residual / (n - 2.0)).sqrt();
+        let slope_se = s / sxx.sqrt();
+        BiasDrift {
+            time_unit: if days.is_some() { "day" } else { "observation" },
+            slope,
+            intercept,
+            slope_se,
+            p_value: t_test_p_value(slope / slope_se, n - 2.0),
+            average_bias: y_mean,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::make_data;
+
+    fn readings(timestamps: &[&str], values: &[f64]) -> Vec<StabilityData> {
+        timestamps
+            .iter()
+            .zip(values.iter())
+            .enumerate()
+            .map(|(i, (timestamp, measured))| StabilityData {
+                data: make_data("Stability", "ref", "A", i + 1, *measured, 10.0).unwrap(),
+                timestamp: timestamp.to_string(),
+            })
+            .collect()
+    }
+
+    /// Flagged points on a chart with center 0 and sigma 1
+    fn flagged(rules: &[RunRule], values: &[f64]) -> Vec<usize> {
+        let mut chart = ControlChart::new("test", Vec::new(), values.to_vec(), 0.0, 3.0, -3.0);
+        chart.apply_rules(rules);
+        let mut flagged = chart
+            .violations
+            .iter()
+            .map(|v| v.index)
+            .collect::<Vec<usize>>();
+        flagged.dedup();
+        flagged
+    }
+
+    #[test]
+    fn points_on_a_limit_are_not_beyond_it() {
+        let rules = [RunRule::BeyondLimits];
+        assert_eq!(flagged(&rules, &[0.0, 3.0, -3.0, 3.01, -3.5]), vec![3, 4]);
+        let rules = [RunRule::TwoOfThree];
+        assert!(flagged(&rules, &[2.0, 2.0, 2.0]).is_empty());
+        assert_eq!(flagged(&rules, &[2.5, 0.0, 2.1]), vec![2]);
+        let rules = [RunRule::FourOfFive];
+        assert!(flagged(&rules, &[1.0, 1.5, 1.5, 1.5, 0.0]).is_empty());
+        assert_eq!(flagged(&rules, &[-1.5, -1.5, 0.0, -1.5, -1.5]), vec![4]);
+    }
+
+    #[test]
+    fn two_of_three_needs_the_same_side() {
+        assert!(flagged(&[RunRule::TwoOfThree], &[2.5, 0.0, -2.5]).is_empty());
+    }
+
+    #[test]
+    fn western_electric_flags_eight_on_one_side() {
+        let values = [0.5, 0.2, 0.8, 0.1, 0.4, 0.3, 0.6, 0.2, -0.5];
+        assert_eq!(flagged(&RuleSet::WesternElectric.rules(), &values), vec![7]);
+        // Nelson needs nine
+        assert!(flagged(&RuleSet::Nelson.rules(), &values).is_empty());
+        // A point on the center line breaks the run
+        let values = [0.5, 0.2, 0.8, 0.0, 0.4, 0.3, 0.6, 0.2, 0.5];
+        assert!(flagged(&RuleSet::WesternElectric.rules(), &values).is_empty());
+    }
+
+    #[test]
+    fn nelson_trend_and_alternation() {
+        let rules = [RunRule::Trend];
+        assert_eq!(flagged(&rules, &[-1.0, -0.5, 0.0, 0.5, 1.0, 1.5]), vec![5]);
+        // A tie ends the trend
+        assert!(flagged(&rules, &[-1.0, -0.5, 0.0, 0.0, 1.0, 1.5]).is_empty());
+        let rules = [RunRule::Alternating];
+        let alternating = (0..14)
+            .map(|i| if i % 2 == 0 { 0.5 } else { -0.5 })
+            .collect::<Vec<f64>>();
+        assert_eq!(flagged(&rules, &alternating), vec![13]);
+        assert!(flagged(&rules, &alternating[..13]).is_empty());
+    }
+
+    #[test]
+    fn nelson_stratification_and_mixture() {
+        let rules = [RunRule::Stratification];
+        let hugging = (0..15)
+            .map(|i| if i % 2 == 0 { 0.5 } else { -0.5 })
+            .collect::<Vec<f64>>();
+        assert_eq!(flagged(&rules, &hugging), vec![14]);
+        let mut on_sigma = hugging.clone();
+        on_sigma[7] = 1.0;
+        assert!(flagged(&rules, &on_sigma).is_empty());
+
+        let rules = [RunRule::Mixture];
+        let mixed = [1.5, -1.5, 2.0, -2.0, 1.2, -1.2, 1.5, -1.5];
+        assert_eq!(flagged(&rules, &mixed), vec![7]);
+        assert!(flagged(&rules, &[1.5, 1.5, 2.0, 2.0, 1.2, 1.2, 1.5, 1.5]).is_empty());
+    }
+
+    #[test]
+    fn individuals_moving_range_limits() {
+        let timestamps = ["1", "2", "3", "4", "5"];
+        let study =
+            StabilityStudy::from_data(&readings(&timestamps, &[1.0, 2.0, 3.0, 2.0, 1.0])).unwrap();
+        assert!((study.location.center - 1.8).abs() < 1e-12);
+        assert!((study.location.ucl - 4.46).abs() < 1e-12);
+        assert!((study.location.lcl + 0.86).abs() < 1e-12);
+        assert_eq!(study.dispersion.values, vec![1.0; 4]);
+        assert!((study.dispersion.center - 1.0).abs() < 1e-12);
+        assert!((study.dispersion.ucl - 3.267).abs() < 1e-12);
+        assert_eq!(study.dispersion.lcl, 0.0);
+    }
+
+    #[test]
+    fn xbar_s_limits() {
+        let timestamps = ["1", "2", "3", "4", "5"];
+        let study = StabilityStudy::from_data(&readings(&timestamps, &[1.0, 3.0, 2.0, 4.0, 9.0]))
+            .unwrap()
+            .with_chart(ChartType::XbarS, 2)
+            .unwrap();
+        assert_eq!(study.location.values, vec![2.0, 3.0]);
+        let s_bar = 2.0_f64.sqrt();
+        assert!((study.dispersion.center - s_bar).abs() < 1e-12);
+        // A3 = 2.659, B3 = 0 and B4 = 3.267 for subgroups of two
+        assert!((study.location.ucl - (2.5 + 2.659 * s_bar)).abs() < 1e-3);
+        assert!((study.location.lcl - (2.5 - 2.659 * s_bar)).abs() < 1e-3);
+        assert!((study.dispersion.ucl - 3.267 * s_bar).abs() < 1e-3);
+        assert_eq!(study.dispersion.lcl, 0.0);
+    }
+
+    #[test]
+    fn chart_positions_in_days_or_points() {
+        let dates = [
+            "2024-03-01",
+            "2024-03-02",
+            "2024-03-04",
+            "2024-03-04T12:00",
+            "2024-03-08",
+        ];
+        let values = [1.0, 2.0, 3.0, 2.0, 1.0];
+        let study = StabilityStudy::from_data(&readings(&dates, &values)).unwrap();
+        assert!(study.has_dates());
+        assert_eq!(
+            study.chart_positions(&study.location),
+            vec![0.0, 1.0, 3.0, 3.5, 7.0]
+        );
+        assert_eq!(
+            study.chart_positions(&study.dispersion),
+            vec![1.0, 3.0, 3.5, 7.0]
+        );
+        let study = study.with_chart(ChartType::XbarS, 2).unwrap();
+        assert_eq!(study.chart_positions(&study.location), vec![0.0, 3.0]);
+
+        let labels = ["a", "b", "c", "d", "e"];
+        let study = StabilityStudy::from_data(&readings(&labels, &values)).unwrap();
+        assert!(!study.has_dates());
+        assert_eq!(
+            study.chart_positions(&study.location),
+            vec![1.0, 2.0, 3.0, 4.0, 5.0]
+        );
+        assert_eq!(
+            study.chart_positions(&study.dispersion),
+            vec![2.0, 3.0, 4.0, 5.0]
+        );
+    }
+}
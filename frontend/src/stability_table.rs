@@ -0,0 +1,196 @@
+use crate::StabilityStudy;
+use eframe::egui;
+/// Control limits, bias drift and run rule violations of a stability study
+pub struct StabilityTableView<'a> {
+    pub striped: bool,
+    pub resizable: bool,
+    pub dataset: &'a Option<StabilityStudy>,
+}
+
+impl Default for StabilityTableView<'_> {
+    fn default() -> Self {
+        Self {
+            striped: true,
+            resizable: true,
+            dataset: &None,
+        }
+    }
+}
+
+impl<'a> StabilityTableView<'a> {
+    pub fn name(&self) -> &'static str {
+        "☰ Stability Study Table"
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        dataset: &'a Option<StabilityStudy>,
+        open: &mut bool,
+    ) {
+        self.dataset = dataset;
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                self.ui(ui);
+            });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.style_mut().override_text_style = Some(egui::style::TextStyle::Monospace);
+        use egui_extras::{Size, StripBuilder};
+        StripBuilder::new(ui)
+            .size(Size::exact(140.0)) // for the limits and drift table
+            .size(Size::remainder().at_least(100.0)) // for the rule violations
+            .vertical(|mut strip| {
+                strip.cell(|ui| {
+                    egui::ScrollArea::horizontal()
+                        .id_salt("limits_scroll")
+                        .show(ui, |ui| {
+                            self.limits_table_ui(ui);
+                        });
+                });
+                strip.cell(|ui| {
+                    egui::ScrollArea::horizontal()
+                        .id_salt("violations_scroll")
+                        .show(ui, |ui| {
+                            self.violations_table_ui(ui);
+                        });
+                });
+            });
+    }
+
+    fn limits_table_ui(&mut self, ui: &mut egui::Ui) {
+        use egui_extras::{Column, TableBuilder};
+
+        let table = TableBuilder::new(ui)
+            .id_salt("limits_table")
+            .striped(self.striped)
+            .cell_layout(egui::Layout::right_to_left(egui::Align::Center))
+            .columns(
+                Column::initial(100.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+                4,
+            )
+            .min_scrolled_height(0.0);
+
+        table
+            .header(20.0, |mut header| {
+                for title in ["Chart", "Center", "UCL", "LCL"] {
+                    header.col(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.strong(title);
+                        });
+                    });
+                }
+            })
+            .body(|mut body| {
+                if let Some(study) = self.dataset {
+                    let row_height = 18.0;
+                    for chart in [&study.location, &study.dispersion] {
+                        body.row(row_height, |mut row| {
+                            row.col(|ui| {
+                                ui.label(chart.name);
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:>9.5}", chart.center));
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:>9.5}", chart.ucl));
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:>9.5}", chart.lcl));
+                            });
+                        });
+                    }
+                    let drift = &study.drift;
+                    let summary = [
+                        ("Reference", format!("{:>9.5}", study.reference)),
+                        ("Avg Bias", format!("{:>9.5}", drift.average_bias)),
+                        (
+                            "Drift",
+                            format!("{:>9.5} per {}", drift.slope, drift.time_unit),
+                        ),
+                        ("Drift p", format!("{:>6.3}", drift.p_value)),
+                    ];
+                    for (label, value) in summary.into_iter() {
+                        body.row(row_height, |mut row| {
+                            row.col(|ui| {
+                                ui.label(label);
+                            });
+                            row.col(|ui| {
+                                ui.label(value);
+                            });
+                            row.col(|ui| {
+                                ui.label("");
+                            });
+                            row.col(|ui| {
+                                ui.label("");
+                            });
+                        });
+                    }
+                }
+            });
+    }
+
+    fn violations_table_ui(&mut self, ui: &mut egui::Ui) {
+        use egui_extras::{Column, TableBuilder};
+
+        let table = TableBuilder::new(ui)
+            .id_salt("violations_table")
+            .striped(self.striped)
+            .cell_layout(egui::Layout::right_to_left(egui::Align::Center))
+            .column(
+                Column::initial(100.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+            )
+            .column(
+                Column::initial(160.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+            )
+            .column(Column::remainder().at_least(100.0).clip(true))
+            .min_scrolled_height(0.0);
+
+        table
+            .header(20.0, |mut header| {
+                for title in ["Chart", "Timestamp", "Rule"] {
+                    header.col(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.strong(title);
+                        });
+                    });
+                }
+            })
+            .body(|mut body| {
+                if let Some(study) = self.dataset {
+                    let row_height = 18.0;
+                    for chart in [&study.location, &study.dispersion] {
+                        for violation in chart.violations.iter() {
+                            body.row(row_height, |mut row| {
+                                row.col(|ui| {
+                                    ui.label(chart.name);
+                                });
+                                row.col(|ui| {
+                                    ui.label(chart.labels[violation.index].as_str());
+                                });
+                                row.col(|ui| {
+                                    ui.colored_label(
+                                        egui::Color32::RED,
+                                        violation.rule.description(),
+                                    );
+                                });
+                            });
+                        }
+                    }
+                }
+            });
+    }
+}
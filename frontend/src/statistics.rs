@@ -1,4 +1,5 @@
-use std::cmp::PartialOrd;
+use statrs::distribution::{ContinuousCDF, FisherSnedecor, Normal, StudentsT};
+use statrs::function::gamma::ln_gamma;
 use std::convert::Into;
 
 pub struct Statistics {
@@ -30,38 +31,207 @@ impl Statistics {
 
     pub fn from_values<T>(mut self, values: &[T]) -> Self
     where
-        T: PartialOrd + Into<f64> + Clone,
+        T: Into<f64> + Clone,
     {
-        let mut stat_values = values.to_owned();
-        stat_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mut stat_values = values
+            .iter()
+            .map(|v| v.clone().into())
+            .collect::<Vec<f64>>();
+        stat_values.sort_by(f64::total_cmp);
         let len = stat_values.len();
         if len < 2 {
             return self;
         }
-        self.min = stat_values[0].clone().into();
-        self.max = stat_values[len - 1].clone().into();
-        self.mean = stat_values
-            .iter()
-            .fold(0.0, |acc, v: &T| acc + v.to_owned().into())
-            / len as f64;
-        self.median = if len % 2 == 0 {
-            (stat_values[len / 2].clone().into() + stat_values[len / 2 + 1].clone().into()) / 2_f64
-        } else {
-            stat_values[len / 2].clone().into()
-        };
-        (self.q1, self.q3) = if len % 2 == 0 {
-            let (left, right) = stat_values.split_at(len / 2 + 1);
-            (
-                left[left.len() / 2].clone().into(),
-                right[right.len() / 2].clone().into(),
-            )
-        } else {
-            let (left, right) = stat_values.split_at(len / 2);
-            (
-                left[left.len() / 2].clone().into(),
-                right[right.len() / 2].clone().into(),
-            )
-        };
+        self.min = stat_values[0];
+        self.max = stat_values[len - 1];
+        self.mean = stat_values.iter().sum::<f64>() / len as f64;
+        self.median = median(&stat_values);
+        // Quartiles are the medians of the lower and upper halves, leaving out
+        // the middle value when the count is odd
+        self.q1 = median(&stat_values[..len / 2]);
+        self.q3 = median(&stat_values[len.div_ceil(2)..]);
         self
     }
 }
+
+/// Median of values sorted in ascending order
+fn median(sorted: &[f64]) -> f64 {
+    let len = sorted.len();
+    if len % 2 == 0 {
+        (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0
+    } else {
+        sorted[len / 2]
+    }
+}
+
+/// Two-sided p-value for a Student's t statistic with `dof` degrees of freedom
+pub fn t_test_p_value(t: f64, dof: f64) -> f64 {
+    match StudentsT::new(0.0, 1.0, dof) {
+        Ok(dist) if t.is_finite() => 2.0 * dist.sf(t.abs()),
+        _ => f64::NAN,
+    }
+}
+
+/// Two-sided critical value of Student's t for significance level `alpha`
+pub fn t_critical(alpha: f64, dof: f64) -> f64 {
+    match StudentsT::new(0.0, 1.0, dof) {
+        Ok(dist) => dist.inverse_cdf(1.0 - alpha / 2.0),
+        _ => f64::NAN,
+    }
+}
+
+/// Upper-tail p-value for an F statistic with (`dof1`, `dof2`) degrees of freedom
+pub fn f_test_p_value(f: f64, dof1: f64, dof2: f64) -> f64 {
+    match FisherSnedecor::new(dof1, dof2) {
+        Ok(dist) if f.is_finite() => dist.sf(f.max(0.0)),
+        _ => f64::NAN,
+    }
+}
+
+/// Standard normal quantile of probability `p`
+pub fn normal_quantile(p: f64) -> f64 {
+    match Normal::new(0.0, 1.0) {
+        Ok(dist) => dist.inverse_cdf(p),
+        _ => f64::NAN,
+    }
+}
+
+/// Anderson-Darling normality test with estimated mean and variance, returning
+/// the statistic A² and its p-value (D'Agostino and Stephens approximation)
+pub fn anderson_darling(values: &[f64]) -> (f64, f64) {
+    let n = values.len();
+    let Ok(standard) = Normal::new(0.0, 1.0) else {
+        return (f64::NAN, f64::NAN);
+    };
+    if n < 3 {
+        return (f64::NAN, f64::NAN);
+    }
+    let nf = n as f64;
+    let mean = values.iter().sum::<f64>() / nf;
+    let stddev = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (nf - 1.0)).sqrt();
+    if stddev <= 0.0 {
+        return (f64::NAN, f64::NAN);
+    }
+    let mut z = values
+        .iter()
+        .map(|v| (v - mean) / stddev)
+        .collect::<Vec<f64>>();
+    z.sort_by(f64::total_cmp);
+    let sum = z
+        .iter()
+        .zip(z.iter().rev())
+        .enumerate()
+        .map(|(i, (low, high))| {
+            (2.0 * i as f64 + 1.0) * (standard.cdf(*low).ln() + standard.sf(*high).ln())
+        })
+        .sum::<f64>();
+    let a2 = -nf - sum / nf;
+    let adjusted = a2 * (1.0 + 0.75 / nf + 2.25 / nf.powi(2));
+    let p_value = if adjusted >= 0.6 {
+        (1.2937 - 5.709 * adjusted + 0.0186 * adjusted.powi(2)).exp()
+    } else if adjusted >= 0.34 {
+        (0.9177 - 4.279 * adjusted - 1.38 * adjusted.powi(2)).exp()
+    } else if adjusted >= 0.2 {
+        1.0 - (-8.318 + 42.796 * adjusted - 59.938 * adjusted.powi(2)).exp()
+    } else {
+        1.0 - (-13.436 + 101.14 * adjusted - 223.73 * adjusted.powi(2)).exp()
+    };
+    (a2, p_value.clamp(0.0, 1.0))
+}
+
+/// Bias correction constant c4 for the standard deviation of `n` samples
+pub fn c4(n: usize) -> f64 {
+    let n = n as f64;
+    (2.0 / (n - 1.0)).sqrt() * (ln_gamma(n / 2.0) - ln_gamma((n - 1.0) / 2.0)).exp()
+}
+
+/// Range chart constant D4 for subgroups of `n`, so that UCL_R = D4 x R-bar.
+/// Subgroups beyond 10 use the value for 10.
+pub fn d4(n: usize) -> f64 {
+    const D4: [f64; 9] = [
+        3.267, 2.574, 2.282, 2.114, 2.004, 1.924, 1.864, 1.816, 1.777,
+    ];
+    D4[n.clamp(2, 10) - 2]
+}
+
+/// Xbar chart constant A2 for subgroups of `n`, so that the limits are
+/// Xbar-bar ± A2 x R-bar. Subgroups beyond 10 use the value for 10.
+pub fn a2(n: usize) -> f64 {
+    const A2: [f64; 9] = [
+        1.880, 1.023, 0.729, 0.577, 0.483, 0.419, 0.373, 0.337, 0.308,
+    ];
+    A2[n.clamp(2, 10) - 2]
+}
+
+/// Range chart constant D3 for subgroups of `n`, so that LCL_R = D3 x R-bar.
+/// Subgroups beyond 10 use the value for 10.
+pub fn d3(n: usize) -> f64 {
+    const D3: [f64; 9] = [0.0, 0.0, 0.0, 0.0, 0.0, 0.076, 0.136, 0.184, 0.223];
+    D3[n.clamp(2, 10) - 2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quartiles_of_even_count() {
+        let stats = Statistics::new().from_values(&[4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.q1, 1.5);
+        assert_eq!(stats.median, 2.5);
+        assert_eq!(stats.q3, 3.5);
+        assert_eq!(stats.max, 4.0);
+        assert_eq!(stats.mean, 2.5);
+    }
+
+    #[test]
+    fn quartiles_of_odd_count() {
+        let stats = Statistics::new().from_values(&[7, 1, 5, 3, 9, 11, 13]);
+        assert_eq!(stats.q1, 3.0);
+        assert_eq!(stats.median, 7.0);
+        assert_eq!(stats.q3, 11.0);
+
+        let stats = Statistics::new().from_values(&[5.0, 4.0, 3.0, 2.0, 1.0]);
+        assert_eq!(stats.q1, 1.5);
+        assert_eq!(stats.median, 3.0);
+        assert_eq!(stats.q3, 4.5);
+    }
+
+    #[test]
+    fn normal_quantiles() {
+        assert!(normal_quantile(0.5).abs() < 1e-12);
+        assert!((normal_quantile(0.975) - 1.959964).abs() < 1e-6);
+        assert!((normal_quantile(0.1) + 1.281552).abs() < 1e-6);
+    }
+
+    #[test]
+    fn anderson_darling_of_uniform_spacing() {
+        // Same approximation as nortest::ad.test(1:10) in R: A = 0.1411, p = 0.9567
+        let values = (1..=10).map(f64::from).collect::<Vec<f64>>();
+        let (a2, p_value) = anderson_darling(&values);
+        assert!((a2 - 0.141109).abs() < 1e-5);
+        assert!((p_value - 0.956658).abs() < 1e-5);
+    }
+
+    #[test]
+    fn anderson_darling_rejects_skewed_values() {
+        let values = [2.1, 2.3, 2.2, 2.9, 3.5, 2.2, 2.4, 5.0, 2.3, 2.2, 2.1, 2.6];
+        let (a2, p_value) = anderson_darling(&values);
+        assert!((a2 - 1.570773).abs() < 1e-5);
+        assert!((p_value - 0.000243).abs() < 1e-6);
+    }
+
+    #[test]
+    fn anderson_darling_needs_spread() {
+        assert!(anderson_darling(&[1.0, 2.0]).1.is_nan());
+        assert!(anderson_darling(&[3.0, 3.0, 3.0]).1.is_nan());
+    }
+
+    #[test]
+    fn fewer_than_two_values_are_left_at_zero() {
+        let stats = Statistics::new().from_values(&[3.0]);
+        assert_eq!(stats.median, 0.0);
+        assert_eq!(stats.q3, 0.0);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{Sigmas, Simulation, Worksheet};
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+/// Candidate crossed design: every operator measures every part `replicates` times
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Design {
+    pub parts: usize,
+    pub operators: usize,
+    pub replicates: usize,
+}
+
+impl Design {
+    pub fn new(parts: usize, operators: usize, replicates: usize) -> Self {
+        Self {
+            parts,
+            operators,
+            replicates,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        format!("{}×{}×{}", self.parts, self.operators, self.replicates)
+    }
+
+    pub fn runs(&self) -> usize {
+        self.parts * self.operators * self.replicates
+    }
+
+    pub fn part_ids(&self) -> Vec<String> {
+        (1..=self.parts).map(|p| format!("part {p}")).collect()
+    }
+
+    /// Operators are lettered A, B, C, ... and numbered beyond Z
+    pub fn operator_ids(&self) -> Vec<String> {
+        (0..self.operators)
+            .map(|o| match u8::try_from(o).ok().filter(|o| *o < 26) {
+                Some(o) => format!("operator {}", (b'A' + o) as char),
+                None => format!("operator {}", o + 1),
+            })
+            .collect()
+    }
+
+    /// Empty data-collection template in standard order, using the same columns as
+    /// the CSV import with `measured` left blank
+    pub fn template_csv(&self, nominal: f64) -> Result<String> {
+        Worksheet::in_standard_order(
+            &self.part_ids(),
+            &self.operator_ids(),
+            self.replicates,
+            nominal,
+        )?
+        .to_csv()
+    }
+}
+
+/// Simulated median and central 95% interval of an estimate
+pub struct Interval {
+    pub lower: f64,
+    pub median: f64,
+    pub upper: f64,
+}
+
+impl Interval {
+    pub(crate) fn from_samples(mut samples: Vec<f64>) -> Self {
+        samples.sort_by(f64::total_cmp);
+        let quantile = |q: f64| samples[((samples.len() - 1) as f64 * q).round() as usize];
+        Self {
+            lower: quantile(0.025),
+            median: quantile(0.5),
+            upper: quantile(0.975),
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.upper - self.lower
+    }
+}
+
+pub struct DesignEstimate {
+    pub design: Design,
+    pub percent_study_var: Interval,
+    pub ndc: Interval,
+}
+
+/// Precision of %Study Var and ndc for candidate designs, estimated by simulating
+/// studies from assumed repeatability and reproducibility.
+pub struct StudyPlan {
+    /// Assumed repeatability, as % of the total study variation
+    pub repeatability: f64,
+    /// Assumed reproducibility, as % of the total study variation
+    pub reproducibility: f64,
+    pub simulations: usize,
+    pub true_percent_study_var: f64,
+    pub true_ndc: f64,
+    pub estimates: Vec<DesignEstimate>,
+}
+
+impl StudyPlan {
+    pub fn from_assumptions(
+        repeatability: f64,
+        reproducibility: f64,
+        designs: &[Design],
+        simulations: usize,
+        seed: u64,
+    ) -> Result<Self> {
+        let sigma_repeatability = repeatability / 100.0;
+        let sigma_reproducibility = reproducibility / 100.0;
+        let gagerr = sigma_repeatability.powi(2) + sigma_reproducibility.powi(2);
+        if repeatability <= 0.0 || reproducibility < 0.0 || gagerr >= 1.0 {
+            bail!("Repeatability and reproducibility must be positive and combine to under 100%");
+        }
+        if simulations < 100 {
+            bail!("Study planning requires at least 100 simulations");
+        }
+        if let Some(d) = designs
+            .iter()
+            .find(|d| d.parts < 2 || d.operators < 2 || d.replicates < 2)
+        {
+            bail!(
+                "Design {} requires at least two parts, operators and replicates",
+                d.label()
+            );
+        }
+        let sigmas = Sigmas {
+            part: (1.0 - gagerr).sqrt(),
+            operator: sigma_reproducibility,
+            interaction: 0.0,
+            repeatability: sigma_repeatability,
+        };
+        // Each design is simulated and analysed as the simulator and the study do,
+        // so the intervals agree with what they report for the same design
+        let estimates = designs
+            .iter()
+            .zip(0u64..)
+            .map(|(design, i)| {
+                let simulation = Simulation::from_sigmas(
+                    sigmas,
+                    *design,
+                    0.0,
+                    simulations,
+                    seed.wrapping_add(i),
+                )?;
+                let Some(ndc) = simulation.ndc_interval() else {
+                    bail!("No simulated {} study had gage variation", design.label());
+                };
+                Ok(DesignEstimate {
+                    design: *design,
+                    percent_study_var: simulation.percent_study_var_interval(),
+                    ndc,
+                })
+            })
+            .collect::<Result<Vec<DesignEstimate>>>()?;
+        Ok(Self {
+            repeatability,
+            reproducibility,
+            simulations,
+            true_percent_study_var: sigmas.percent_study_var(),
+            true_ndc: sigmas.ndc(),
+            estimates,
+        })
+    }
+}
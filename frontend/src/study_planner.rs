@@ -0,0 +1,257 @@
+use crate::{Design, StudyPlan};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// Study-planning window: compares the precision of candidate designs and
+/// produces an empty data-collection template for the chosen one.
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct StudyPlanner {
+    pub repeatability: f64,
+    pub reproducibility: f64,
+    pub simulations: usize,
+    pub seed: u64,
+    pub designs: Vec<Design>,
+    pub selected: usize,
+    pub nominal: f64,
+    #[serde(skip)]
+    plan: Option<StudyPlan>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl Default for StudyPlanner {
+    fn default() -> Self {
+        Self {
+            repeatability: 15.0,
+            reproducibility: 10.0,
+            simulations: 1000,
+            seed: 1,
+            designs: vec![
+                Design::new(10, 3, 2),
+                Design::new(5, 3, 3),
+                Design::new(10, 2, 3),
+            ],
+            selected: 0,
+            nominal: 0.0,
+            plan: None,
+            error: None,
+        }
+    }
+}
+
+impl StudyPlanner {
+    pub fn name(&self) -> &'static str {
+        "☰ Study Planner"
+    }
+
+    /// Returns the file name and content of a template when one is requested
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) -> Option<(String, String)> {
+        let mut template = None;
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                template = self.ui(ui);
+            });
+        template
+    }
+
+    pub fn selected_design(&self) -> Option<Design> {
+        self.designs.get(self.selected).copied()
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<(String, String)> {
+        egui::Grid::new("planner_assumptions").show(ui, |ui| {
+            ui.label("Repeatability (% of total SD): ");
+            ui.add(
+                egui::DragValue::new(&mut self.repeatability)
+                    .speed(0.5)
+                    .range(0.1..=99.0),
+            );
+            ui.end_row();
+            ui.label("Reproducibility (% of total SD): ");
+            ui.add(
+                egui::DragValue::new(&mut self.reproducibility)
+                    .speed(0.5)
+                    .range(0.0..=99.0),
+            );
+            ui.end_row();
+            ui.label("Simulations: ");
+            ui.add(
+                egui::DragValue::new(&mut self.simulations)
+                    .speed(100)
+                    .range(100..=100000),
+            );
+            ui.end_row();
+            ui.label("Seed: ");
+            ui.add(egui::DragValue::new(&mut self.seed));
+            ui.end_row();
+        });
+        ui.separator();
+        ui.strong("Candidate designs");
+        let mut remove = None;
+        egui::Grid::new("planner_designs").show(ui, |ui| {
+            ui.label("");
+            ui.strong("Parts");
+            ui.strong("Operators");
+            ui.strong("Replicates");
+            ui.label("");
+            ui.end_row();
+            for (i, design) in self.designs.iter_mut().enumerate() {
+                ui.radio_value(&mut self.selected, i, "");
+                ui.add(egui::DragValue::new(&mut design.parts).range(2..=100));
+                ui.add(egui::DragValue::new(&mut design.operators).range(2..=26));
+                ui.add(egui::DragValue::new(&mut design.replicates).range(2..=20));
+                if ui.button("✖").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(i) = remove {
+            self.designs.remove(i);
+            self.selected = self.selected.min(self.designs.len().saturating_sub(1));
+            self.plan = None;
+        }
+        let mut template = None;
+        ui.horizontal(|ui| {
+            if ui.button("Add design").clicked() {
+                let design = self
+                    .designs
+                    .last()
+                    .copied()
+                    .unwrap_or(Design::new(10, 3, 2));
+                self.designs.push(design);
+            }
+            if ui
+                .add_enabled(!self.designs.is_empty(), egui::Button::new("Estimate..."))
+                .clicked()
+            {
+                self.estimate();
+            }
+        });
+        if let Some(err) = &self.error {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+        if let Some(plan) = &self.plan {
+            ui.separator();
+            ui.label(format!(
+                "True %Study Var: {:.2}   True ndc: {:.2}   ({} simulations)",
+                plan.true_percent_study_var, plan.true_ndc, plan.simulations
+            ));
+            egui::ScrollArea::horizontal().show(ui, |ui| {
+                Self::table_ui(ui, plan);
+            });
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Nominal: ");
+            ui.add(egui::DragValue::new(&mut self.nominal).speed(0.1));
+            if let Some(design) = self.selected_design() {
+                if ui
+                    .button(format!("Save {} template...", design.label()))
+                    .clicked()
+                {
+                    match design.template_csv(self.nominal) {
+                        Ok(csv) => {
+                            template = Some((format!("gage_study_{}.csv", design.label()), csv));
+                        }
+                        Err(e) => {
+                            tracing::error!("Design::template_csv: {e:?}");
+                            self.error = Some(e.to_string());
+                        }
+                    }
+                }
+            }
+        });
+        template
+    }
+
+    fn estimate(&mut self) {
+        match StudyPlan::from_assumptions(
+            self.repeatability,
+            self.reproducibility,
+            &self.designs,
+            self.simulations,
+            self.seed,
+        ) {
+            Ok(plan) => {
+                self.plan = Some(plan);
+                self.error = None;
+            }
+            Err(e) => {
+                tracing::error!("StudyPlan::from_assumptions: {e:?}");
+                self.plan = None;
+                self.error = Some(e.to_string());
+            }
+        }
+    }
+
+    fn table_ui(ui: &mut egui::Ui, plan: &StudyPlan) {
+        use egui_extras::{Column, TableBuilder};
+
+        let table = TableBuilder::new(ui)
+            .striped(true)
+            .cell_layout(egui::Layout::right_to_left(egui::Align::Center))
+            .columns(
+                Column::initial(100.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+                6,
+            )
+            .min_scrolled_height(0.0);
+
+        table
+            .header(40.0, |mut header| {
+                for (title, subtitle) in [
+                    ("Design", "(P×O×R)"),
+                    ("Runs", ""),
+                    ("%Study Var", "(95% range)"),
+                    ("Width", "(%SV)"),
+                    ("ndc", "(95% range)"),
+                    ("Width", "(ndc)"),
+                ] {
+                    header.col(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.strong(title);
+                            ui.strong(subtitle);
+                        });
+                    });
+                }
+            })
+            .body(|mut body| {
+                let row_height = 18.0;
+                for estimate in plan.estimates.iter() {
+                    body.row(row_height, |mut row| {
+                        row.col(|ui| {
+                            ui.label(estimate.design.label());
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{:>4}", estimate.design.runs()));
+                        });
+                        row.col(|ui| {
+                            ui.label(format!(
+                                "{:>6.2} - {:>6.2}",
+                                estimate.percent_study_var.lower, estimate.percent_study_var.upper
+                            ));
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{:>6.2}", estimate.percent_study_var.width()));
+                        });
+                        row.col(|ui| {
+                            ui.label(format!(
+                                "{:>5.2} - {:>5.2}",
+                                estimate.ndc.lower, estimate.ndc.upper
+                            ));
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{:>5.2}", estimate.ndc.width()));
+                        });
+                    });
+                }
+            });
+    }
+}
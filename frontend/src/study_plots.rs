@@ -1,16 +1,42 @@
-use crate::Statistics;
+use crate::{
+    BarSummary, BoxSummary, ExportSettings, Figure, Mark, Marker, OperatorChart, OutlierScreen,
+    SpecLimits, Statistics, VarianceComponents,
+};
 use eframe::egui;
-use egui_plot::{BoxElem, BoxPlot, BoxSpread, Legend, Line, MarkerShape, Plot, Points};
-use gage_study::dataset::DataSet;
-use std::{collections::HashMap, collections::HashSet, ops::RangeInclusive};
+use gage_study::{data::Data, dataset::DataSet};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, collections::HashMap, collections::HashSet};
 
 pub enum PlotType {
     PartMeasurement,
     OperatorMeasurement,
+    XbarByOperator,
+    RangeByOperator,
+    PartOperatorInteraction,
+    ComponentsOfVariation,
+}
+
+/// What the user asked for in a plot window, for the app to act on
+#[derive(Default)]
+pub struct PlotOutput {
+    /// Indices into `data` of the readings behind clicked or brushed points
+    pub selected: Option<Vec<usize>>,
+    /// File name and content of an exported image
+    pub export: Option<(String, Vec<u8>)>,
 }
 
 pub struct StudyPlots<'a> {
     pub dataset: Option<&'a DataSet>,
+    /// Readings behind `dataset`, for the charts of part-by-operator subgroups
+    pub data: &'a [Data],
+    /// Variance components, for the components of variation chart
+    pub components: Option<&'a VarianceComponents>,
+    /// Rows of `data` flagged as suspect readings
+    pub outliers: Vec<usize>,
+    /// Rows of `data` selected in the data table or any plot
+    pub selection: Vec<usize>,
+    /// Nominal, specification limits and units of the readings
+    pub spec_limits: SpecLimits,
     pub plot_type: PlotType,
 }
 
@@ -18,6 +44,11 @@ impl Default for StudyPlots<'_> {
     fn default() -> Self {
         Self {
             dataset: None,
+            data: &[],
+            components: None,
+            outliers: Vec::new(),
+            selection: Vec::new(),
+            spec_limits: SpecLimits::default(),
             plot_type: PlotType::PartMeasurement,
         }
     }
@@ -28,7 +59,50 @@ impl<'a> StudyPlots<'a> {
         match self.plot_type {
             PlotType::PartMeasurement => "☰ Part Measurements",
             PlotType::OperatorMeasurement => "☰ Operator Measurements",
+            PlotType::XbarByOperator => "☰ Xbar Chart by Operator",
+            PlotType::RangeByOperator => "☰ R Chart by Operator",
+            PlotType::PartOperatorInteraction => "☰ Part × Operator Interaction",
+            PlotType::ComponentsOfVariation => "☰ Components of Variation",
+        }
+    }
+
+    /// Id of the plot, which also keys its settings in egui's memory
+    fn id(&self) -> &'static str {
+        match self.plot_type {
+            PlotType::PartMeasurement => "part_msmt",
+            PlotType::OperatorMeasurement => "operator_msmt",
+            PlotType::XbarByOperator => "xbar_by_operator",
+            PlotType::RangeByOperator => "range_by_operator",
+            PlotType::PartOperatorInteraction => "part_operator_interaction",
+            PlotType::ComponentsOfVariation => "components_of_variation",
+        }
+    }
+
+    pub fn with_data(mut self, data: &'a [Data]) -> Self {
+        self.data = data;
+        self
+    }
+
+    pub fn with_components(mut self, components: &'a Option<VarianceComponents>) -> Self {
+        self.components = components.as_ref();
+        self
+    }
+
+    pub fn with_outliers(mut self, screen: &Option<OutlierScreen>) -> Self {
+        if let Some(screen) = screen {
+            self.outliers = screen.rows();
         }
+        self
+    }
+
+    pub fn with_selection(mut self, selection: &[usize]) -> Self {
+        self.selection = selection.to_owned();
+        self
+    }
+
+    pub fn with_spec_limits(mut self, spec_limits: &SpecLimits) -> Self {
+        self.spec_limits = spec_limits.clone();
+        self
     }
 
     pub fn show(
@@ -37,30 +111,62 @@ impl<'a> StudyPlots<'a> {
         dataset: &'a Option<DataSet>,
         plot_type: PlotType,
         open: &mut bool,
-    ) {
+    ) -> PlotOutput {
         self.dataset = dataset.as_ref();
         self.plot_type = plot_type;
+        let mut output = PlotOutput::default();
         egui::Window::new(self.name())
             .open(open)
             .resizable(true)
             .default_width(400.0)
             .show(ctx, |ui| {
-                self.ui(ui);
+                output = self.ui(ui);
             });
+        output
     }
 
-    pub fn ui(&mut self, ui: &mut egui::Ui) {
-        if let Some(dataset) = self.dataset {
-            match self.plot_type {
-                PlotType::PartMeasurement => Self::part_measurement_plot(ui, dataset),
-                PlotType::OperatorMeasurement => Self::operator_measurement_plot(ui, dataset),
-            };
-        }
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> PlotOutput {
+        let mut output = PlotOutput::default();
+        let Some(dataset) = self.dataset else {
+            return output;
+        };
+        let title = self.name().trim_start_matches("☰ ");
+        let figure = match self.plot_type {
+            PlotType::PartMeasurement => Some(Self::part_measurement_plot(
+                ui,
+                title,
+                dataset,
+                self.data,
+                &self.outliers,
+                &self.spec_limits,
+            )),
+            PlotType::OperatorMeasurement => Some(Self::operator_measurement_plot(
+                ui,
+                title,
+                dataset,
+                self.data,
+                &self.outliers,
+                &self.spec_limits,
+            )),
+            PlotType::XbarByOperator => Self::xbar_by_operator_plot(ui, title, self.data),
+            PlotType::RangeByOperator => Self::range_by_operator_plot(ui, title, self.data),
+            PlotType::PartOperatorInteraction => {
+                Some(Self::interaction_plot(title, dataset, self.data))
+            }
+            PlotType::ComponentsOfVariation => self
+                .components
+                .map(|components| Self::components_of_variation_plot(title, components)),
+        };
+        let Some(figure) = figure else {
+            return output;
+        };
+        output.export = ExportSettings::ui(ui, self.id(), &figure);
+        output.selected = figure.show(ui, self.id(), self.data, &self.selection);
+        output
     }
 
-    fn part_measurement_plot(ui: &mut egui::Ui, dataset: &DataSet) -> egui::Response {
-        // Put part ids into a vec so they can be sorted
-        // Also used for x-axis labels
+    /// Sorted part ids, which place the parts along the x-axis and label it
+    fn sorted_part_ids(dataset: &DataSet) -> Vec<String> {
         let part_hs = dataset
             .parts
             .iter()
@@ -68,64 +174,11 @@ impl<'a> StudyPlots<'a> {
             .collect::<HashSet<String>>();
         let mut part_vec = Vec::from_iter(part_hs);
         part_vec.sort();
-        // Map part ids to sorted indices
-        let part_map = part_vec
-            .iter()
-            .enumerate()
-            .map(|(i, v)| (v.to_owned(), i))
-            .collect::<HashMap<String, usize>>();
-        // Create points and average points
-        let mut points: Vec<[f64; 2]> = Vec::new();
-        let mut avg_points: Vec<[f64; 2]> = Vec::new();
-        for part in dataset.parts.iter() {
-            let idx = part_map.get(&part.id).unwrap_or(&0).to_owned() + 1;
-            points.extend_from_slice(
-                &part
-                    .values
-                    .iter()
-                    .map(|v| [idx as f64, *v])
-                    .collect::<Vec<[f64; 2]>>(),
-            );
-            let avg = part.values.iter().sum::<f64>() / part.values.len() as f64;
-            avg_points.push([idx as f64, avg]);
-        }
-        avg_points.sort_by(|a, b| a[0].partial_cmp(&b[0]).unwrap());
-        // Create markers
-        let markers = Points::new("part", points)
-            .shape(MarkerShape::Circle)
-            .radius(4.0)
-            .filled(false)
-            .color(egui::Color32::GRAY);
-        let avg_markers = Points::new("part", avg_points.clone())
-            .shape(MarkerShape::Circle)
-            .radius(4.0)
-            .highlight(true)
-            .color(egui::Color32::BLUE);
-        let avg_line = Line::new("part", avg_points)
-            .color(egui::Color32::BLUE)
-            .highlight(true);
-        Plot::new("part_msmt")
-            .legend(Legend::default())
-            .x_axis_formatter(move |x, _range: &RangeInclusive<f64>| {
-                if x.value.floor() >= 1.0 && (x.value.floor() as usize - 1 < part_vec.len()) {
-                    let idx = x.value.floor() as usize - 1;
-                    format!("Part {}", part_vec[idx])
-                } else {
-                    "".to_string()
-                }
-            })
-            .set_margin_fraction(egui::Vec2 { x: 0.1, y: 0.1 })
-            .show(ui, |plot_ui| {
-                plot_ui.points(markers);
-                plot_ui.points(avg_markers);
-                plot_ui.line(avg_line);
-            })
-            .response
+        part_vec
     }
 
-    fn operator_measurement_plot(ui: &mut egui::Ui, dataset: &DataSet) -> egui::Response {
-        // Put part ids into a vec so they can be sorted
-        // Also used for x-axis labels
+    /// Sorted operator ids, which place the operators along the x-axis and label it
+    fn sorted_operator_ids(dataset: &DataSet) -> Vec<String> {
         let op_hs = dataset
             .operators
             .iter()
@@ -133,40 +186,702 @@ impl<'a> StudyPlots<'a> {
             .collect::<HashSet<String>>();
         let mut op_vec = Vec::from_iter(op_hs);
         op_vec.sort();
+        op_vec
+    }
+
+    fn part_measurement_plot(
+        ui: &mut egui::Ui,
+        title: &str,
+        dataset: &DataSet,
+        data: &[Data],
+        outliers: &[usize],
+        spec_limits: &SpecLimits,
+    ) -> Figure {
+        let options = MeasurementOptions::load(
+            ui,
+            "part_msmt",
+            MeasurementOptions::default(),
+            "By operator within part",
+        );
+        let part_vec = Self::sorted_part_ids(dataset);
         // Map part ids to sorted indices
+        let part_map = part_vec
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.to_owned(), i))
+            .collect::<HashMap<String, usize>>();
+        let groups = if options.grouped {
+            let op_vec = Self::sorted_operator_ids(dataset);
+            Self::grouped(
+                data,
+                &part_map,
+                &op_vec,
+                |d| (&d.part, &d.operator),
+                "Operator",
+            )
+        } else {
+            Self::ungrouped(data, &part_map, |d| &d.part, |_| "part".to_owned(), "part")
+        };
+        let labels = part_vec
+            .iter()
+            .map(|p| format!("Part {p}"))
+            .collect::<Vec<String>>();
+        let mut figure = Figure::new(title, "Part", &spec_limits.axis_label("Measured"));
+        // Drawn first so the readings lie over the tolerance band
+        if let (Some(lsl), Some(usl)) = (spec_limits.lsl, spec_limits.usl) {
+            figure.push(
+                "tolerance",
+                egui::Color32::DARK_GREEN,
+                Mark::Band {
+                    lower: lsl,
+                    upper: usl,
+                },
+            );
+            figure.push("LSL / USL", egui::Color32::RED, dashed(lsl));
+            figure.push("LSL / USL", egui::Color32::RED, dashed(usl));
+        }
+        if let Some(nominal) = spec_limits.nominal {
+            figure.push(
+                "nominal",
+                egui::Color32::DARK_GREEN,
+                Mark::HLine {
+                    y: nominal,
+                    width: 1.5,
+                    dashed: false,
+                },
+            );
+        }
+        measurement_figure(figure, groups, options, labels, outliers)
+    }
+
+    fn operator_measurement_plot(
+        ui: &mut egui::Ui,
+        title: &str,
+        dataset: &DataSet,
+        data: &[Data],
+        outliers: &[usize],
+        spec_limits: &SpecLimits,
+    ) -> Figure {
+        let options = MeasurementOptions::load(
+            ui,
+            "operator_msmt",
+            MeasurementOptions {
+                points: false,
+                means: false,
+                lines: false,
+                boxes: true,
+                grouped: false,
+            },
+            "By part within operator",
+        );
+        let op_vec = Self::sorted_operator_ids(dataset);
+        // Map operator ids to sorted indices
         let op_map = op_vec
             .iter()
             .enumerate()
             .map(|(i, v)| (v.to_owned(), i))
             .collect::<HashMap<String, usize>>();
-        // Create points and average points
-        let mut boxes: Vec<BoxPlot> = Vec::new();
-        for op in dataset.operators.iter() {
-            let idx = op_map.get(&op.id).unwrap_or(&0).to_owned() + 1;
-            let stats = Statistics::new().from_values(&op.values);
-            let box_elem = BoxElem::new(
-                idx as f64,
-                BoxSpread::new(stats.min, stats.q1, stats.median, stats.q3, stats.max),
+        let groups = if options.grouped {
+            let part_vec = Self::sorted_part_ids(dataset);
+            Self::grouped(data, &op_map, &part_vec, |d| (&d.operator, &d.part), "Part")
+        } else {
+            Self::ungrouped(
+                data,
+                &op_map,
+                |d| &d.operator,
+                |id| format!("Operator {id}"),
+                "operator mean",
             )
-            .name(format!("Operator {}", op.id).as_str());
-            boxes.push(BoxPlot::new(format!("Operator {}", op.id), vec![box_elem]));
-        }
-        // Create markers
-        Plot::new("operator_msmt")
-            .legend(Legend::default())
-            .x_axis_formatter(move |x, _range: &RangeInclusive<f64>| {
-                if x.value.floor() >= 1.0 && (x.value.floor() as usize - 1 < op_vec.len()) {
-                    let idx = x.value.floor() as usize - 1;
-                    op_vec[idx].to_string()
-                } else {
-                    "".to_string()
-                }
+        };
+        measurement_figure(
+            Figure::new(title, "Operator", &spec_limits.axis_label("Measured")),
+            groups,
+            options,
+            op_vec,
+            outliers,
+        )
+    }
+
+    /// Readings at each position of one factor: `key` returns the id of a reading
+    /// and `name` the legend entry of an id
+    fn ungrouped(
+        data: &[Data],
+        positions: &HashMap<String, usize>,
+        key: impl Fn(&Data) -> &String,
+        name: impl Fn(&str) -> String,
+        line: &str,
+    ) -> Vec<Group> {
+        let mut cells = BTreeMap::<usize, (&String, Vec<usize>)>::new();
+        for (row, d) in data.iter().enumerate() {
+            let id = key(d);
+            let Some(i) = positions.get(id) else {
+                continue;
+            };
+            cells.entry(*i).or_insert((id, Vec::new())).1.push(row);
+        }
+        cells
+            .into_iter()
+            .map(|(i, (id, rows))| Group {
+                x: (i + 1) as f64,
+                name: name(id),
+                line: line.to_owned(),
+                color: None,
+                values: rows.iter().map(|row| data[*row].measured).collect(),
+                rows,
             })
-            .show(ui, |plot_ui| {
-                for boxx in boxes.into_iter() {
-                    plot_ui.box_plot(boxx);
+            .collect()
+    }
+
+    /// Readings split by a second factor within each position of the first:
+    /// `key` returns the (outer, inner) ids of a reading, and each inner id is a
+    /// series offset around the outer position
+    fn grouped(
+        data: &[Data],
+        outer: &HashMap<String, usize>,
+        inner: &[String],
+        key: impl Fn(&Data) -> (&String, &String),
+        inner_label: &str,
+    ) -> Vec<Group> {
+        let width = 0.8 / inner.len().max(1) as f64;
+        let mut cells = BTreeMap::<(usize, usize), Vec<usize>>::new();
+        for (row, d) in data.iter().enumerate() {
+            let (outer_id, inner_id) = key(d);
+            let (Some(i), Some(j)) = (
+                outer.get(outer_id),
+                inner.iter().position(|id| id == inner_id),
+            ) else {
+                continue;
+            };
+            cells.entry((*i, j)).or_default().push(row);
+        }
+        cells
+            .into_iter()
+            .map(|((i, j), rows)| {
+                let name = format!("{inner_label} {}", inner[j]);
+                Group {
+                    x: (i + 1) as f64 + (j as f64 + 0.5) * width - 0.4,
+                    line: name.clone(),
+                    name,
+                    color: Some(operator_color(j)),
+                    values: rows.iter().map(|row| data[*row].measured).collect(),
+                    rows,
                 }
             })
-            .response
+            .collect()
+    }
+
+    /// Part labels at the subgroup positions of an operator chart
+    fn subgroup_ticks(chart: &OperatorChart, positions: &[f64]) -> Vec<(f64, String)> {
+        chart
+            .subgroups
+            .iter()
+            .zip(positions.iter())
+            .map(|(s, x)| (*x, s.part.clone()))
+            .collect()
+    }
+
+    fn xbar_by_operator_plot(ui: &mut egui::Ui, title: &str, data: &[Data]) -> Option<Figure> {
+        let chart = match OperatorChart::from_data(data) {
+            Ok(chart) => chart,
+            Err(e) => {
+                ui.label(e.to_string());
+                return None;
+            }
+        };
+        let positions = chart.positions();
+        let outside = chart.xbar_outside_count();
+        let total = chart.subgroups.len();
+        let summary = format!(
+            "{outside} of {total} part averages ({:.0}%) outside the limits",
+            outside as f64 / total as f64 * 100.0
+        );
+        if outside * 2 > total {
+            ui.label(format!("{summary}: the gage discriminates between parts"));
+        } else {
+            ui.colored_label(
+                egui::Color32::ORANGE,
+                format!(
+                    "⚠ {summary}: fewer than half, the gage may not discriminate between parts"
+                ),
+            );
+        }
+        let mut figure = Figure::new(title, "Part (grouped by operator)", "Part Average")
+            .with_x_ticks(Self::subgroup_ticks(&chart, &positions));
+        figure.push(
+            "Xbar-bar",
+            egui::Color32::DARK_GREEN,
+            dashed(chart.grand_mean),
+        );
+        figure.push("UCL / LCL", egui::Color32::RED, dashed(chart.xbar_ucl));
+        figure.push("UCL / LCL", egui::Color32::RED, dashed(chart.xbar_lcl));
+        // One line of part averages per operator, out-of-limit averages in red
+        for (i, operator) in chart.operators.iter().enumerate() {
+            let (points, rows) = chart
+                .subgroups
+                .iter()
+                .zip(positions.iter())
+                .filter(|(s, _)| &s.operator == operator)
+                .map(|(s, x)| ([*x, s.mean], s.rows.clone()))
+                .unzip::<_, _, Vec<[f64; 2]>, Vec<Vec<usize>>>();
+            push_line_with_markers(
+                &mut figure,
+                format!("Operator {operator}"),
+                i,
+                points,
+                rows,
+                false,
+            );
+        }
+        let flagged = chart
+            .subgroups
+            .iter()
+            .zip(positions.iter())
+            .filter(|(s, _)| chart.xbar_outside(s))
+            .map(|(s, x)| ([*x, s.mean], s.rows.clone()))
+            .unzip::<_, _, Vec<[f64; 2]>, Vec<Vec<usize>>>();
+        figure.push(
+            "outside limits",
+            egui::Color32::RED,
+            Mark::Points {
+                points: flagged.0,
+                marker: Marker::Circle,
+                radius: 5.0,
+                filled: false,
+                highlight: false,
+                rows: flagged.1,
+            },
+        );
+        Some(figure)
+    }
+
+    /// One line per operator through that operator's part averages; lines that are
+    /// not parallel show a part-by-operator interaction
+    fn interaction_plot(title: &str, dataset: &DataSet, data: &[Data]) -> Figure {
+        let part_vec = Self::sorted_part_ids(dataset);
+        let part_map = part_vec
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.as_str(), i))
+            .collect::<HashMap<&str, usize>>();
+        // Rows of the readings of each operator on each part
+        let mut cells = BTreeMap::<&str, BTreeMap<usize, Vec<usize>>>::new();
+        for (row, d) in data.iter().enumerate() {
+            let Some(idx) = part_map.get(d.part.as_str()) else {
+                continue;
+            };
+            cells
+                .entry(d.operator.as_str())
+                .or_default()
+                .entry(*idx + 1)
+                .or_default()
+                .push(row);
+        }
+        let mut figure = Figure::new(title, "Part", "Average").with_x_ticks(
+            part_vec
+                .iter()
+                .enumerate()
+                .map(|(i, p)| ((i + 1) as f64, format!("Part {p}")))
+                .collect(),
+        );
+        for (i, (operator, parts)) in cells.into_iter().enumerate() {
+            let (points, rows) = parts
+                .into_iter()
+                .map(|(idx, rows)| {
+                    let sum = rows.iter().map(|row| data[*row].measured).sum::<f64>();
+                    ([idx as f64, sum / rows.len() as f64], rows)
+                })
+                .unzip::<_, _, Vec<[f64; 2]>, Vec<Vec<usize>>>();
+            push_line_with_markers(
+                &mut figure,
+                format!("Operator {operator}"),
+                i,
+                points,
+                rows,
+                false,
+            );
+        }
+        figure
+    }
+
+    /// %Contribution, %Study Var and %Tolerance side by side for each headline
+    /// source, with the 10% and 30% acceptance lines
+    fn components_of_variation_plot(title: &str, components: &VarianceComponents) -> Figure {
+        let sources = components.headline_sources();
+        let mut metrics = vec![
+            (
+                "%Contribution",
+                sources
+                    .iter()
+                    .map(|(_, varcomp, _)| components.percent_contribution(*varcomp))
+                    .collect::<Vec<f64>>(),
+            ),
+            (
+                "%Study Var",
+                sources
+                    .iter()
+                    .map(|(_, _, stddev)| components.percent_study_var(*stddev))
+                    .collect::<Vec<f64>>(),
+            ),
+        ];
+        if components.tolerance > 0.0 {
+            metrics.push((
+                "%Tolerance",
+                sources
+                    .iter()
+                    .map(|(_, _, stddev)| components.percent_tolerance(*stddev))
+                    .collect::<Vec<f64>>(),
+            ));
+        }
+        let mut figure = Figure::new(title, "Source", "Percent").with_x_ticks(
+            sources
+                .iter()
+                .enumerate()
+                .map(|(i, (name, _, _))| ((i + 1) as f64, name.to_string()))
+                .collect(),
+        );
+        let width = 0.8 / metrics.len() as f64;
+        for (m, (name, values)) in metrics.into_iter().enumerate() {
+            let offset = (m as f64 + 0.5) * width - 0.4;
+            let bars = values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| BarSummary {
+                    x: i as f64 + 1.0 + offset,
+                    height: *value,
+                    width,
+                    name: format!("{} {name}", sources[i].0),
+                })
+                .collect();
+            figure.push(name, operator_color(m), Mark::Bars(bars));
+        }
+        figure.push("10% acceptable", egui::Color32::DARK_GREEN, dashed(10.0));
+        figure.push("30% unacceptable", egui::Color32::RED, dashed(30.0));
+        figure
+    }
+
+    fn range_by_operator_plot(ui: &mut egui::Ui, title: &str, data: &[Data]) -> Option<Figure> {
+        let chart = match OperatorChart::from_data(data) {
+            Ok(chart) => chart,
+            Err(e) => {
+                ui.label(e.to_string());
+                return None;
+            }
+        };
+        let positions = chart.positions();
+        ui.label(
+            chart
+                .operator_average_ranges()
+                .iter()
+                .map(|(operator, range)| format!("R-bar {operator}: {range:.4}"))
+                .collect::<Vec<String>>()
+                .join("   "),
+        );
+        let inconsistent = chart.inconsistent_operators();
+        if inconsistent.is_empty() {
+            ui.label("All ranges in control: repeatability is consistent between operators");
+        } else {
+            ui.colored_label(
+                egui::Color32::ORANGE,
+                format!(
+                    "⚠ Out-of-control ranges for {}: repeatability is inconsistent. Click a point to show its readings.",
+                    inconsistent.join(", ")
+                ),
+            );
+        }
+        let mut figure = Figure::new(title, "Part (grouped by operator)", "Range")
+            .with_x_ticks(Self::subgroup_ticks(&chart, &positions));
+        figure.push(
+            "R-bar",
+            egui::Color32::DARK_GREEN,
+            dashed(chart.average_range),
+        );
+        figure.push("UCL / LCL", egui::Color32::RED, dashed(chart.range_ucl));
+        figure.push("UCL / LCL", egui::Color32::RED, dashed(chart.range_lcl));
+        for (i, operator) in chart.operators.iter().enumerate() {
+            let (points, rows) = chart
+                .subgroups
+                .iter()
+                .zip(positions.iter())
+                .filter(|(s, _)| &s.operator == operator)
+                .map(|(s, x)| ([*x, s.range], s.rows.clone()))
+                .unzip::<_, _, Vec<[f64; 2]>, Vec<Vec<usize>>>();
+            let highlight = inconsistent.contains(&operator.as_str());
+            push_line_with_markers(
+                &mut figure,
+                format!("Operator {operator}"),
+                i,
+                points,
+                rows,
+                highlight,
+            );
+        }
+        let flagged = chart
+            .subgroups
+            .iter()
+            .zip(positions.iter())
+            .filter(|(s, _)| chart.range_outside(s))
+            .map(|(s, x)| ([*x, s.range], s.rows.clone()))
+            .unzip::<_, _, Vec<[f64; 2]>, Vec<Vec<usize>>>();
+        figure.push(
+            "out of control",
+            egui::Color32::RED,
+            Mark::Points {
+                points: flagged.0,
+                marker: Marker::Circle,
+                radius: 6.0,
+                filled: true,
+                highlight: false,
+                rows: flagged.1,
+            },
+        );
+        Some(figure)
+    }
+}
+
+/// Distinct color per operator, spread around the hue circle
+fn operator_color(index: usize) -> egui::Color32 {
+    let hue = (index as f32 * 0.618_034).fract();
+    egui::ecolor::Hsva::new(hue, 0.85, 0.5, 1.0).into()
+}
+
+/// Dashed reference line at `y`
+fn dashed(y: f64) -> Mark {
+    Mark::HLine {
+        y,
+        width: 1.0,
+        dashed: true,
+    }
+}
+
+/// Line through `points` with a marker on each, in the color of series `index`;
+/// `rows` are the readings behind each point
+fn push_line_with_markers(
+    figure: &mut Figure,
+    name: String,
+    index: usize,
+    points: Vec<[f64; 2]>,
+    rows: Vec<Vec<usize>>,
+    highlight: bool,
+) {
+    let color = operator_color(index);
+    figure.push(
+        name.as_str(),
+        color,
+        Mark::Line {
+            points: points.clone(),
+            highlight,
+            dashed: false,
+        },
+    );
+    figure.push(
+        name,
+        color,
+        Mark::Points {
+            points,
+            marker: Marker::Circle,
+            radius: 4.0,
+            filled: true,
+            highlight: false,
+            rows,
+        },
+    );
+}
+
+/// Display toggles of a measurement plot, kept in egui's persisted memory
+#[derive(Clone, Copy, Deserialize, Serialize)]
+struct MeasurementOptions {
+    points: bool,
+    means: bool,
+    lines: bool,
+    boxes: bool,
+    /// Split each position by the other factor
+    grouped: bool,
+}
+
+impl Default for MeasurementOptions {
+    fn default() -> Self {
+        Self {
+            points: true,
+            means: true,
+            lines: true,
+            boxes: false,
+            grouped: false,
+        }
+    }
+}
+
+impl MeasurementOptions {
+    /// Loads the options of plot `id`, shows their toggles and stores any change
+    fn load(ui: &mut egui::Ui, id: &str, default: Self, grouped_label: &str) -> Self {
+        let id = egui::Id::new(("measurement_options", id));
+        let mut options = ui
+            .data_mut(|d| d.get_persisted::<Self>(id))
+            .unwrap_or(default);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut options.points, "Points");
+            ui.checkbox(&mut options.means, "Means");
+            ui.checkbox(&mut options.lines, "Lines");
+            ui.checkbox(&mut options.boxes, "Box plots");
+            ui.checkbox(&mut options.grouped, grouped_label);
+        });
+        ui.data_mut(|d| d.insert_persisted(id, options));
+        options
+    }
+}
+
+/// Readings drawn at one x position of a measurement plot
+struct Group {
+    x: f64,
+    /// Legend entry of the points, means and box
+    name: String,
+    /// Means with the same key are joined by a line
+    line: String,
+    color: Option<egui::Color32>,
+    values: Vec<f64>,
+    /// Indices of `values` in the plotted data
+    rows: Vec<usize>,
+}
+
+/// Points, means, connecting lines and box plots of the groups, as enabled in
+/// `options`, with the readings in `outliers` marked; `labels` name the integer
+/// x positions from 1
+fn measurement_figure(
+    figure: Figure,
+    groups: Vec<Group>,
+    options: MeasurementOptions,
+    labels: Vec<String>,
+    outliers: &[usize],
+) -> Figure {
+    let mut figure = figure.with_x_ticks(
+        labels
+            .into_iter()
+            .enumerate()
+            .map(|(i, label)| ((i + 1) as f64, label))
+            .collect(),
+    );
+    let box_width = groups
+        .windows(2)
+        .map(|w| (w[1].x - w[0].x).abs())
+        .filter(|d| *d > 0.0)
+        .fold(1.0, f64::min)
+        * 0.8;
+    let mut boxes = BTreeMap::<String, (Option<egui::Color32>, Vec<BoxSummary>)>::new();
+    let mut points =
+        BTreeMap::<String, (Option<egui::Color32>, Vec<[f64; 2]>, Vec<Vec<usize>>)>::new();
+    let mut means =
+        BTreeMap::<String, (Option<egui::Color32>, Vec<[f64; 2]>, Vec<Vec<usize>>)>::new();
+    let mut lines = BTreeMap::<String, (Option<egui::Color32>, Vec<[f64; 2]>)>::new();
+    let mut suspect = (Vec::new(), Vec::new());
+    for group in groups.iter() {
+        suspect.extend(
+            group
+                .rows
+                .iter()
+                .zip(group.values.iter())
+                .filter(|(row, _)| outliers.contains(row))
+                .map(|(row, v)| ([group.x, *v], vec![*row])),
+        );
+        let stats = Statistics::new().from_values(&group.values);
+        boxes
+            .entry(group.name.clone())
+            .or_insert((group.color, Vec::new()))
+            .1
+            .push(BoxSummary {
+                x: group.x,
+                width: box_width,
+                min: stats.min,
+                q1: stats.q1,
+                median: stats.median,
+                q3: stats.q3,
+                max: stats.max,
+                name: group.name.clone(),
+            });
+        let entry =
+            points
+                .entry(group.name.clone())
+                .or_insert((group.color, Vec::new(), Vec::new()));
+        entry.1.extend(group.values.iter().map(|v| [group.x, *v]));
+        entry.2.extend(group.rows.iter().map(|row| vec![*row]));
+        let mean = [
+            group.x,
+            group.values.iter().sum::<f64>() / group.values.len() as f64,
+        ];
+        let entry =
+            means
+                .entry(group.name.clone())
+                .or_insert((group.color, Vec::new(), Vec::new()));
+        entry.1.push(mean);
+        entry.2.push(group.rows.clone());
+        lines
+            .entry(group.line.clone())
+            .or_insert((group.color, Vec::new()))
+            .1
+            .push(mean);
+    }
+    if options.boxes {
+        for (i, (name, (color, elems))) in boxes.into_iter().enumerate() {
+            figure.push(name, color.unwrap_or(operator_color(i)), Mark::Boxes(elems));
+        }
+    }
+    if options.points {
+        for (name, (color, values, rows)) in points.into_iter() {
+            figure.push(
+                name,
+                color.unwrap_or(egui::Color32::GRAY),
+                Mark::Points {
+                    points: values,
+                    marker: Marker::Circle,
+                    radius: 4.0,
+                    filled: false,
+                    highlight: false,
+                    rows,
+                },
+            );
+        }
+    }
+    if options.lines {
+        for (name, (color, mut values)) in lines.into_iter() {
+            values.sort_by(|a, b| a[0].total_cmp(&b[0]));
+            figure.push(
+                name,
+                color.unwrap_or(egui::Color32::BLUE),
+                Mark::Line {
+                    points: values,
+                    highlight: true,
+                    dashed: false,
+                },
+            );
+        }
+    }
+    if options.means {
+        for (name, (color, values, rows)) in means.into_iter() {
+            figure.push(
+                name,
+                color.unwrap_or(egui::Color32::BLUE),
+                Mark::Points {
+                    points: values,
+                    marker: Marker::Circle,
+                    radius: 4.0,
+                    filled: true,
+                    highlight: true,
+                    rows,
+                },
+            );
+        }
+    }
+    if !suspect.0.is_empty() {
+        figure.push(
+            "suspect reading",
+            egui::Color32::RED,
+            Mark::Points {
+                points: suspect.0,
+                marker: Marker::Square,
+                radius: 5.0,
+                filled: true,
+                highlight: false,
+                rows: suspect.1,
+            },
+        );
     }
+    figure
 }
@@ -0,0 +1,93 @@
+use crate::{ExportSettings, Figure, Mark, Marker, Type1Study};
+use eframe::egui;
+
+#[derive(Default)]
+pub struct Type1RunChart<'a> {
+    pub study: Option<&'a Type1Study>,
+}
+
+impl<'a> Type1RunChart<'a> {
+    pub fn name(&self) -> &'static str {
+        "☰ Type 1 Run Chart"
+    }
+
+    /// Shows the run chart, returning the file name and content of an exported image
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        study: &'a Option<Type1Study>,
+        open: &mut bool,
+    ) -> Option<(String, Vec<u8>)> {
+        self.study = study.as_ref();
+        let mut export = None;
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                export = self.ui(ui);
+            });
+        export
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<(String, Vec<u8>)> {
+        let figure = Self::run_chart(self.study?);
+        let export = ExportSettings::ui(ui, "type1_run_chart", &figure);
+        figure.show(ui, "type1_run_chart", &[], &[]);
+        export
+    }
+
+    fn run_chart(study: &Type1Study) -> Figure {
+        let points = study
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| [(i + 1) as f64, *v])
+            .collect::<Vec<[f64; 2]>>();
+        let band = study.reference_band();
+        let band_name = format!("Ref ± {:.2} x Tol", study.k_percent / 200.0);
+        let mut figure = Figure::new("Type 1 Run Chart", "Observation", "Measured");
+        figure.push(
+            "reference",
+            egui::Color32::DARK_GREEN,
+            Mark::HLine {
+                y: study.reference,
+                width: 1.5,
+                dashed: false,
+            },
+        );
+        for y in [study.reference + band, study.reference - band] {
+            figure.push(
+                &band_name,
+                egui::Color32::RED,
+                Mark::HLine {
+                    y,
+                    width: 1.0,
+                    dashed: true,
+                },
+            );
+        }
+        figure.push(
+            "measured",
+            egui::Color32::BLUE,
+            Mark::Line {
+                points: points.clone(),
+                highlight: false,
+                dashed: false,
+            },
+        );
+        figure.push(
+            "measured",
+            egui::Color32::BLUE,
+            Mark::Points {
+                points,
+                marker: Marker::Circle,
+                radius: 3.0,
+                filled: true,
+                highlight: false,
+                rows: Vec::new(),
+            },
+        );
+        figure
+    }
+}
@@ -0,0 +1,166 @@
+use crate::statistics::t_test_p_value;
+use anyhow::{Result, bail};
+use gage_study::data::Data;
+
+/// Type 1 gage study: one reference part measured repeatedly by one operator.
+/// The reference value is taken from `Data::nominal`.
+pub struct Type1Study {
+    pub part: String,
+    pub operator: String,
+    pub values: Vec<f64>,
+    pub reference: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    pub bias: f64,
+    pub t_statistic: f64,
+    pub p_value: f64,
+    pub tolerance: f64,
+    pub k_percent: f64,
+    pub spread: f64,
+    pub cg: f64,
+    pub cgk: f64,
+}
+
+impl Type1Study {
+    pub fn from_data(data: &[Data]) -> Result<Self> {
+        let Some(first) = data.first() else {
+            bail!("Type 1 study requires at least one measurement");
+        };
+        if data.iter().any(|d| d.part != first.part) {
+            bail!("Type 1 study requires a single reference part");
+        }
+        if data.iter().any(|d| d.operator != first.operator) {
+            bail!("Type 1 study requires a single operator");
+        }
+        if data.iter().any(|d| d.nominal != first.nominal) {
+            bail!("Type 1 study requires a single reference (nominal) value");
+        }
+        if data.len() < 2 {
+            bail!("Type 1 study requires at least two measurements");
+        }
+        let values = data.iter().map(|d| d.measured).collect::<Vec<f64>>();
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let stddev = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+        let bias = mean - first.nominal;
+        // Two-sided one-sample t-test of H0: bias = 0
+        let t_statistic = bias / (stddev / n.sqrt());
+        let p_value = t_test_p_value(t_statistic, n - 1.0);
+        let mut study = Self {
+            part: first.part.clone(),
+            operator: first.operator.clone(),
+            values,
+            reference: first.nominal,
+            mean,
+            stddev,
+            bias,
+            t_statistic,
+            p_value,
+            tolerance: 1.0,
+            k_percent: 20.0,
+            spread: 6.0,
+            cg: 0.0,
+            cgk: 0.0,
+        };
+        study.evaluate();
+        Ok(study)
+    }
+
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self.evaluate();
+        self
+    }
+
+    pub fn with_k_percent(mut self, k_percent: f64) -> Self {
+        self.k_percent = k_percent;
+        self.evaluate();
+        self
+    }
+
+    pub fn with_spread(mut self, spread: f64) -> Self {
+        self.spread = spread;
+        self.evaluate();
+        self
+    }
+
+    /// Half-width of the band around the reference used by Cgk, i.e. K/2 x Tol
+    pub fn reference_band(&self) -> f64 {
+        self.k_percent / 200.0 * self.tolerance
+    }
+
+    pub fn bias_significant(&self, alpha: f64) -> bool {
+        self.p_value < alpha
+    }
+
+    fn evaluate(&mut self) {
+        let k = self.k_percent / 100.0;
+        self.cg = k * self.tolerance / (self.spread * self.stddev);
+        self.cgk = (k / 2.0 * self.tolerance - self.bias.abs()) / (self.spread / 2.0 * self.stddev);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::make_data;
+
+    /// Ten readings of a 10.000 reference with mean 10.0007 and standard
+    /// deviation 0.0022136, shifted by `offset`
+    fn data(offset: f64) -> Vec<Data> {
+        [
+            10.002, 9.998, 10.003, 10.001, 9.999, 10.002, 10.000, 10.004, 9.997, 10.001,
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, v)| make_data("", "master", "A", i + 1, v + offset, 10.0).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn capability_and_bias_test() {
+        let study = Type1Study::from_data(&data(0.0))
+            .unwrap()
+            .with_tolerance(0.1);
+        assert!((study.bias - 0.0007).abs() < 1e-9);
+        assert!((study.stddev - 0.0022136).abs() < 1e-7);
+        // t = 1 with 9 degrees of freedom
+        assert!((study.t_statistic - 1.0).abs() < 1e-9);
+        assert!((study.p_value - 0.343436).abs() < 1e-6);
+        assert!(!study.bias_significant(0.05));
+        assert!((study.cg - 1.505847).abs() < 1e-6);
+        assert!((study.cgk - 1.400437).abs() < 1e-6);
+    }
+
+    #[test]
+    fn significant_bias_lowers_cgk_only() {
+        let study = Type1Study::from_data(&data(0.003))
+            .unwrap()
+            .with_tolerance(0.1);
+        assert!((study.t_statistic - 5.285714).abs() < 1e-6);
+        assert!((study.p_value - 0.000503).abs() < 1e-6);
+        assert!(study.bias_significant(0.05));
+        assert!((study.cg - 1.505847).abs() < 1e-6);
+        assert!((study.cgk - 0.948683).abs() < 1e-6);
+    }
+
+    #[test]
+    fn k_percent_and_spread_change_the_indices() {
+        let study = Type1Study::from_data(&data(0.0))
+            .unwrap()
+            .with_tolerance(0.1)
+            .with_k_percent(15.0)
+            .with_spread(4.0);
+        assert!((study.reference_band() - 0.0075).abs() < 1e-12);
+        assert!((study.cg - 1.694077).abs() < 1e-6);
+        assert!((study.cgk - 1.535963).abs() < 1e-6);
+    }
+
+    #[test]
+    fn mixed_references_are_rejected() {
+        let mut data = data(0.0);
+        data.push(make_data("", "master", "A", 11, 10.0, 10.5).unwrap());
+        assert!(Type1Study::from_data(&data).is_err());
+        assert!(Type1Study::from_data(&data[..1]).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::Type1Study;
+use eframe::egui;
+/// Bias, its t-test and the Cg/Cgk capability indices of a type 1 gage study
+pub struct Type1TableView<'a> {
+    pub striped: bool,
+    pub resizable: bool,
+    pub dataset: &'a Option<Type1Study>,
+}
+
+impl Default for Type1TableView<'_> {
+    fn default() -> Self {
+        Self {
+            striped: true,
+            resizable: true,
+            dataset: &None,
+        }
+    }
+}
+
+impl<'a> Type1TableView<'a> {
+    pub fn name(&self) -> &'static str {
+        "☰ Type 1 Gage Study"
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, dataset: &'a Option<Type1Study>, open: &mut bool) {
+        self.dataset = dataset;
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(300.0)
+            .show(ctx, |ui| {
+                self.ui(ui);
+            });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.style_mut().override_text_style = Some(egui::style::TextStyle::Monospace);
+        use egui_extras::{Size, StripBuilder};
+        StripBuilder::new(ui)
+            .size(Size::remainder().at_least(100.0)) // for the table
+            .size(Size::exact(20.0)) // for the bias test summary
+            .vertical(|mut strip| {
+                strip.cell(|ui| {
+                    egui::ScrollArea::horizontal().show(ui, |ui| {
+                        self.table_ui(ui);
+                    });
+                });
+                strip.cell(|ui| {
+                    if let Some(study) = self.dataset {
+                        if study.bias_significant(0.05) {
+                            ui.colored_label(egui::Color32::RED, "Bias is significant (p < 0.05)");
+                        } else {
+                            ui.label("Bias is not significant (p >= 0.05)");
+                        }
+                    }
+                });
+            });
+    }
+
+    fn table_ui(&mut self, ui: &mut egui::Ui) {
+        use egui_extras::{Column, TableBuilder};
+
+        let table = TableBuilder::new(ui)
+            .striped(self.striped)
+            .cell_layout(egui::Layout::right_to_left(egui::Align::Center))
+            .column(
+                Column::initial(140.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+            )
+            .column(
+                Column::initial(100.0)
+                    .at_least(40.0)
+                    .resizable(true)
+                    .clip(true),
+            )
+            .min_scrolled_height(0.0);
+
+        table
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.strong("Statistic");
+                    });
+                });
+                header.col(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.strong("Value");
+                    });
+                });
+            })
+            .body(|mut body| {
+                if let Some(study) = self.dataset {
+                    let row_height = 18.0;
+                    let rows = [
+                        ("Reference", format!("{:>10.5}", study.reference)),
+                        ("Mean", format!("{:>10.5}", study.mean)),
+                        ("StdDev (s)", format!("{:>10.6}", study.stddev)),
+                        (
+                            "Study Var",
+                            format!("{:>10.5}", study.spread * study.stddev),
+                        ),
+                        ("Bias", format!("{:>10.6}", study.bias)),
+                        ("T", format!("{:>10.4}", study.t_statistic)),
+                        ("p", format!("{:>10.4}", study.p_value)),
+                        ("Observations", format!("{:>10}", study.values.len())),
+                        ("Tolerance", format!("{:>10.5}", study.tolerance)),
+                        ("K (%Tol)", format!("{:>10.1}", study.k_percent)),
+                        ("Cg", format!("{:>10.3}", study.cg)),
+                        ("Cgk", format!("{:>10.3}", study.cgk)),
+                    ];
+                    for (source, value) in rows.into_iter() {
+                        body.row(row_height, |mut row| {
+                            row.col(|ui| {
+                                ui.label(source);
+                            });
+                            row.col(|ui| {
+                                ui.label(value);
+                            });
+                        });
+                    }
+                }
+            });
+    }
+}
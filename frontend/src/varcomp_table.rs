@@ -1,10 +1,54 @@
+use crate::VarianceComponents;
 use eframe::egui;
-use gage_study::study_evaluation::StudyEvaluation;
+
+/// Source column label, with a warning badge when a negative estimate was set to zero
+pub(crate) fn source_label(ui: &mut egui::Ui, name: &str, adjusted: bool) {
+    if adjusted {
+        ui.colored_label(egui::Color32::ORANGE, "⚠")
+            .on_hover_text("Negative estimate set to zero");
+    }
+    ui.label(name);
+}
+
+/// Height of `summary_ui`, one line per note shown
+pub(crate) fn summary_height(study: &Option<VarianceComponents>) -> f32 {
+    let lines = study.as_ref().map_or(1, |study| {
+        1 + usize::from(study.estimation_warning.is_some())
+            + usize::from(!study.adjusted_sources().is_empty())
+            + usize::from(!study.fixed_terms.is_empty())
+    });
+    20.0 * lines as f32
+}
+
+/// Estimation method and an explanation of any components set to zero or
+/// left out of Gage R&R
+pub(crate) fn summary_ui(ui: &mut egui::Ui, study: &VarianceComponents) {
+    ui.label(format!("Estimation method: {}", study.method.label()));
+    if let Some(warning) = &study.estimation_warning {
+        ui.colored_label(egui::Color32::RED, format!("⚠ {warning}"));
+    }
+    let adjusted = study.adjusted_sources();
+    if !adjusted.is_empty() {
+        ui.colored_label(
+            egui::Color32::ORANGE,
+            format!(
+                "⚠ Negative estimate set to zero (AIAG), totals recalculated: {}",
+                adjusted.join(", ")
+            ),
+        );
+    }
+    if !study.fixed_terms.is_empty() {
+        ui.label(format!(
+            "Fixed terms, not counted in Gage R&R: {}",
+            study.fixed_terms.join(", ")
+        ));
+    }
+}
 /// Shows off a table with dynamic layout
 pub struct VarCompTableView<'a> {
     pub striped: bool,
     pub resizable: bool,
-    pub dataset: &'a Option<StudyEvaluation>,
+    pub dataset: &'a Option<VarianceComponents>,
 }
 
 impl Default for VarCompTableView<'_> {
@@ -25,7 +69,7 @@ impl<'a> VarCompTableView<'a> {
     pub fn show(
         &mut self,
         ctx: &egui::Context,
-        dataset: &'a Option<StudyEvaluation>,
+        dataset: &'a Option<VarianceComponents>,
         open: &mut bool,
     ) {
         self.dataset = dataset;
@@ -43,12 +87,18 @@ impl<'a> VarCompTableView<'a> {
         use egui_extras::{Size, StripBuilder};
         StripBuilder::new(ui)
             .size(Size::remainder().at_least(100.0)) // for the table
+            .size(Size::exact(summary_height(self.dataset))) // for the estimation method and adjustments
             .vertical(|mut strip| {
                 strip.cell(|ui| {
                     egui::ScrollArea::horizontal().show(ui, |ui| {
                         self.table_ui(ui);
                     });
                 });
+                strip.cell(|ui| {
+                    if let Some(study) = self.dataset {
+                        summary_ui(ui, study);
+                    }
+                });
             });
     }
 
@@ -118,7 +168,11 @@ impl<'a> VarCompTableView<'a> {
                     });
                     body.row(row_height, |mut row| {
                         row.col(|ui| {
-                            ui.label("Repeatability");
+                            source_label(
+                                ui,
+                                "Repeatability",
+                                study.total_gagerr.repeatability.adjusted,
+                            );
                         });
                         row.col(|ui| {
                             ui.label(format!("{:>9.7}", study.total_gagerr.repeatability.varcomp));
@@ -132,10 +186,14 @@ impl<'a> VarCompTableView<'a> {
                             ));
                         });
                     });
-                    if study.use_interaction {
+                    if study.show_reproducibility {
                         body.row(row_height, |mut row| {
                             row.col(|ui| {
-                                ui.label("Reproducibility");
+                                source_label(
+                                    ui,
+                                    "Reproducibility",
+                                    study.total_gagerr.reproducibility.adjusted,
+                                );
                             });
                             row.col(|ui| {
                                 ui.label(format!(
@@ -152,10 +210,26 @@ impl<'a> VarCompTableView<'a> {
                                 ));
                             });
                         });
+                        for (name, component) in study.reproducibility_terms.iter() {
+                            body.row(row_height, |mut row| {
+                                row.col(|ui| {
+                                    source_label(ui, name, component.adjusted);
+                                });
+                                row.col(|ui| {
+                                    ui.label(format!("{:>9.7}", component.varcomp));
+                                });
+                                row.col(|ui| {
+                                    ui.label(format!(
+                                        "{:>6.2}",
+                                        component.varcomp / study.total_variation.varcomp * 100.0
+                                    ));
+                                });
+                            });
+                        }
                     }
                     body.row(row_height, |mut row| {
                         row.col(|ui| {
-                            ui.label("Part-to-Part");
+                            source_label(ui, "Part-to-Part", study.part_to_part.adjusted);
                         });
                         row.col(|ui| {
                             ui.label(format!("{:>9.7}", study.part_to_part.varcomp));
@@ -0,0 +1,326 @@
+use crate::{ExpandedAnova, NestedAnova, Reml, ResolutionCheck};
+use gage_study::{anova::Anova, data::Data, study_evaluation::StudyEvaluation};
+use std::collections::BTreeSet;
+
+pub struct VarianceComponent {
+    pub varcomp: f64,
+    pub stddev: f64,
+    /// True when a negative estimate was set to zero
+    pub adjusted: bool,
+}
+
+impl VarianceComponent {
+    /// Negative estimates are set to zero, as recommended by the AIAG MSA manual
+    pub fn new(varcomp: f64) -> Self {
+        let adjusted = varcomp < 0.0;
+        let varcomp = varcomp.max(0.0);
+        Self {
+            varcomp,
+            stddev: varcomp.sqrt(),
+            adjusted,
+        }
+    }
+}
+
+pub struct GageComponent {
+    pub varcomp: f64,
+    pub stddev: f64,
+    pub repeatability: VarianceComponent,
+    pub reproducibility: VarianceComponent,
+}
+
+/// How the variance components were estimated
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EstimationMethod {
+    /// Expected mean squares of a balanced ANOVA
+    Anova,
+    /// Restricted maximum likelihood, used for unbalanced designs
+    Reml,
+}
+
+impl EstimationMethod {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EstimationMethod::Anova => "ANOVA (expected mean squares)",
+            EstimationMethod::Reml => "REML (unbalanced design)",
+        }
+    }
+}
+
+/// Variance components shown by `VarCompTableView` and `GageEvalTableView`,
+/// independent of the design (crossed, nested or expanded) they were estimated from.
+pub struct VarianceComponents {
+    pub total_gagerr: GageComponent,
+    pub part_to_part: VarianceComponent,
+    pub total_variation: VarianceComponent,
+    /// Breakdown of reproducibility by factor, when the design provides one
+    pub reproducibility_terms: Vec<(String, VarianceComponent)>,
+    /// Fixed terms of the design, which have no variance component and are not
+    /// counted in Gage R&R
+    pub fixed_terms: Vec<String>,
+    pub show_reproducibility: bool,
+    pub method: EstimationMethod,
+    /// Why the estimates should not be taken as final, e.g. REML not converging
+    pub estimation_warning: Option<String>,
+    pub tolerance: f64,
+    pub process_variation: f64,
+    /// Historical process standard deviation, the basis of %Process
+    pub historical_stddev: Option<f64>,
+    /// Gage resolution and discrimination of the readings behind the estimates
+    pub resolution: Option<ResolutionCheck>,
+}
+
+impl VarianceComponents {
+    /// Reproducibility is rebuilt from the Operator and Part*Operator mean
+    /// squares of `anova`, so a negative term is set to zero on its own rather
+    /// than hidden in the sum; `data` are the balanced readings behind it.
+    pub fn from_study_evaluation(study: &StudyEvaluation, anova: &Anova, data: &[Data]) -> Self {
+        let parts = data
+            .iter()
+            .map(|d| d.part.as_str())
+            .collect::<BTreeSet<&str>>();
+        let operators = data
+            .iter()
+            .map(|d| d.operator.as_str())
+            .collect::<BTreeSet<&str>>();
+        let parts = parts.len().max(1) as f64;
+        let operators = operators.len().max(1) as f64;
+        let replicates = data.len() as f64 / (parts * operators);
+        let repeatability = study.total_gagerr.repeatability.varcomp;
+        let reproducibility_terms = if study.use_interaction {
+            vec![
+                (
+                    "Operator".to_owned(),
+                    VarianceComponent::new(
+                        (anova.meansq_operators - anova.meansq_part_operator)
+                            / (parts * replicates),
+                    ),
+                ),
+                (
+                    "Part*Operator".to_owned(),
+                    VarianceComponent::new(
+                        (anova.meansq_part_operator - repeatability) / replicates,
+                    ),
+                ),
+            ]
+        } else {
+            // The interaction is pooled into repeatability
+            vec![(
+                "Operator".to_owned(),
+                VarianceComponent::new(
+                    (anova.meansq_operators - repeatability) / (parts * replicates),
+                ),
+            )]
+        };
+        let mut components = Self::from_terms(
+            repeatability,
+            reproducibility_terms,
+            study.part_to_part.varcomp,
+        );
+        components.show_reproducibility = study.use_interaction;
+        components.tolerance = study.tolerance;
+        components.process_variation = study.process_variation;
+        components
+    }
+
+    pub fn from_nested_anova(anova: &NestedAnova) -> Self {
+        let repeatability = anova.meansq_repeatability;
+        let part_to_part =
+            (anova.meansq_parts - anova.meansq_repeatability) / anova.replicates as f64;
+        let reproducibility = (anova.meansq_operators - anova.meansq_parts)
+            / (anova.parts_per_operator * anova.replicates) as f64;
+        Self::from_varcomps(repeatability, reproducibility, part_to_part)
+    }
+
+    pub fn from_expanded_anova(anova: &ExpandedAnova) -> Self {
+        let mut part_to_part = 0.0;
+        let mut reproducibility_terms = Vec::new();
+        let mut fixed_terms = Vec::new();
+        for term in anova.terms.iter() {
+            let Some(varcomp) = term.varcomp else {
+                fixed_terms.push(term.name.clone());
+                continue;
+            };
+            if term.is_part() {
+                part_to_part = varcomp;
+            } else {
+                reproducibility_terms.push((term.name.clone(), VarianceComponent::new(varcomp)));
+            }
+        }
+        let mut components = Self::from_terms(
+            anova.meansq_repeatability,
+            reproducibility_terms,
+            part_to_part,
+        );
+        components.fixed_terms = fixed_terms;
+        components
+    }
+
+    pub fn from_reml(reml: &Reml) -> Self {
+        let reproducibility_terms = vec![
+            ("Operator".to_owned(), VarianceComponent::new(reml.operator)),
+            (
+                "Part*Operator".to_owned(),
+                VarianceComponent::new(reml.part_operator),
+            ),
+        ];
+        let mut components = Self::from_terms(reml.repeatability, reproducibility_terms, reml.part);
+        components.method = EstimationMethod::Reml;
+        if !reml.converged {
+            components.estimation_warning = Some(format!(
+                "REML did not converge after {} iterations, the estimates are not final",
+                reml.iterations
+            ));
+        }
+        components
+    }
+
+    /// Reproducibility is the sum of the (zero-clamped) terms; it is flagged as
+    /// adjusted when any of its terms was.
+    fn from_terms(
+        repeatability: f64,
+        reproducibility_terms: Vec<(String, VarianceComponent)>,
+        part_to_part: f64,
+    ) -> Self {
+        let reproducibility = reproducibility_terms
+            .iter()
+            .map(|(_, c)| c.varcomp)
+            .sum::<f64>();
+        let mut components = Self::from_varcomps(repeatability, reproducibility, part_to_part);
+        components.total_gagerr.reproducibility.adjusted =
+            reproducibility_terms.iter().any(|(_, c)| c.adjusted);
+        components.reproducibility_terms = reproducibility_terms;
+        components
+    }
+
+    /// Negative components are set to zero before the totals are formed, so
+    /// the totals and percentages stay consistent with the displayed components.
+    fn from_varcomps(repeatability: f64, reproducibility: f64, part_to_part: f64) -> Self {
+        let repeatability = VarianceComponent::new(repeatability);
+        let reproducibility = VarianceComponent::new(reproducibility);
+        let part_to_part = VarianceComponent::new(part_to_part);
+        let gagerr = repeatability.varcomp + reproducibility.varcomp;
+        Self {
+            total_variation: VarianceComponent::new(gagerr + part_to_part.varcomp),
+            total_gagerr: GageComponent {
+                varcomp: gagerr,
+                stddev: gagerr.sqrt(),
+                repeatability,
+                reproducibility,
+            },
+            part_to_part,
+            reproducibility_terms: Vec::new(),
+            fixed_terms: Vec::new(),
+            show_reproducibility: true,
+            method: EstimationMethod::Anova,
+            estimation_warning: None,
+            tolerance: 1.0,
+            process_variation: 5.15,
+            historical_stddev: None,
+            resolution: None,
+        }
+    }
+
+    /// Names of the components whose negative estimate was set to zero
+    pub fn adjusted_sources(&self) -> Vec<&str> {
+        let mut sources = Vec::new();
+        if self.total_gagerr.repeatability.adjusted {
+            sources.push("Repeatability");
+        }
+        sources.extend(
+            self.reproducibility_terms
+                .iter()
+                .filter(|(_, c)| c.adjusted)
+                .map(|(name, _)| name.as_str()),
+        );
+        if self.total_gagerr.reproducibility.adjusted && self.reproducibility_terms.is_empty() {
+            sources.push("Reproducibility");
+        }
+        if self.part_to_part.adjusted {
+            sources.push("Part-to-Part");
+        }
+        sources
+    }
+
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn with_process_variation(mut self, process_variation: f64) -> Self {
+        self.process_variation = process_variation;
+        self
+    }
+
+    pub fn with_historical_stddev(mut self, historical_stddev: Option<f64>) -> Self {
+        self.historical_stddev = historical_stddev.filter(|s| *s > 0.0);
+        self
+    }
+
+    pub fn with_resolution(mut self, resolution: ResolutionCheck) -> Self {
+        self.resolution = Some(resolution);
+        self
+    }
+
+    /// Process variation spread, from the historical stddev when given and the
+    /// study's total variation otherwise
+    pub fn process_spread(&self) -> f64 {
+        self.process_variation
+            * self
+                .historical_stddev
+                .unwrap_or(self.total_variation.stddev)
+    }
+
+    /// Warnings when the gauge is too coarse for the tolerance or the process
+    pub fn resolution_warnings(&self) -> Vec<String> {
+        self.resolution.as_ref().map_or(Vec::new(), |check| {
+            check.warnings(self.tolerance, self.process_spread())
+        })
+    }
+
+    /// Headline sources as (name, varcomp, stddev): Gage R&R, Repeatability,
+    /// Reproducibility and Part-to-Part
+    pub fn headline_sources(&self) -> [(&'static str, f64, f64); 4] {
+        [
+            (
+                "Gage R&R",
+                self.total_gagerr.varcomp,
+                self.total_gagerr.stddev,
+            ),
+            (
+                "Repeatability",
+                self.total_gagerr.repeatability.varcomp,
+                self.total_gagerr.repeatability.stddev,
+            ),
+            (
+                "Reproducibility",
+                self.total_gagerr.reproducibility.varcomp,
+                self.total_gagerr.reproducibility.stddev,
+            ),
+            (
+                "Part-to-Part",
+                self.part_to_part.varcomp,
+                self.part_to_part.stddev,
+            ),
+        ]
+    }
+
+    pub fn percent_contribution(&self, varcomp: f64) -> f64 {
+        varcomp / self.total_variation.varcomp * 100.0
+    }
+
+    pub fn percent_study_var(&self, stddev: f64) -> f64 {
+        stddev / self.total_variation.stddev * 100.0
+    }
+
+    pub fn percent_tolerance(&self, stddev: f64) -> f64 {
+        stddev * self.process_variation / self.tolerance * 100.0
+    }
+
+    /// Percentage of the historical process variation (6 x the historical
+    /// stddev) taken up by a component's 6 x stddev spread
+    pub fn percent_process(&self, stddev: f64) -> Option<f64> {
+        self.historical_stddev
+            .map(|historical| stddev / historical * 100.0)
+    }
+}
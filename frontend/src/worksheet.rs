@@ -0,0 +1,156 @@
+use crate::escape_markup;
+use anyhow::{Result, bail};
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+/// One planned measurement; `measured` is left for the operator to fill in
+#[derive(Clone)]
+pub struct WorksheetRow {
+    pub run: usize,
+    pub name: String,
+    pub part: String,
+    pub operator: String,
+    pub replicate: usize,
+    pub nominal: f64,
+}
+
+/// Randomized data-collection worksheet for a crossed study. Each replicate is a
+/// trial in which the operators take turns in random order, each measuring every
+/// part once in its own random order, so trials are not confounded with time.
+pub struct Worksheet {
+    pub seed: u64,
+    pub rows: Vec<WorksheetRow>,
+}
+
+impl Worksheet {
+    pub fn from_plan(
+        parts: &[String],
+        operators: &[String],
+        replicates: usize,
+        nominal: f64,
+        seed: u64,
+    ) -> Result<Self> {
+        if parts.is_empty() || operators.is_empty() || replicates == 0 {
+            bail!("Worksheet requires at least one part, operator and replicate");
+        }
+        let mut unique = parts.to_vec();
+        unique.sort();
+        unique.dedup();
+        if unique.len() != parts.len() {
+            bail!("Part IDs must be unique");
+        }
+        let mut unique = operators.to_vec();
+        unique.sort();
+        unique.dedup();
+        if unique.len() != operators.len() {
+            bail!("Operator IDs must be unique");
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut rows = Vec::new();
+        for replicate in 1..=replicates {
+            let mut operator_order = operators.iter().collect::<Vec<&String>>();
+            operator_order.shuffle(&mut rng);
+            for operator in operator_order.into_iter() {
+                let mut part_order = parts.iter().collect::<Vec<&String>>();
+                part_order.shuffle(&mut rng);
+                for part in part_order.into_iter() {
+                    rows.push(WorksheetRow {
+                        run: rows.len() + 1,
+                        name: format!("{part} {operator} {replicate}"),
+                        part: part.clone(),
+                        operator: operator.clone(),
+                        replicate,
+                        nominal,
+                    });
+                }
+            }
+        }
+        Ok(Self { seed, rows })
+    }
+
+    /// Worksheet in standard order, operator by operator and part by part with
+    /// the replicates together. Nothing is randomized, so the seed is zero.
+    pub fn in_standard_order(
+        parts: &[String],
+        operators: &[String],
+        replicates: usize,
+        nominal: f64,
+    ) -> Result<Self> {
+        let mut rows = Vec::new();
+        for operator in operators.iter() {
+            for part in parts.iter() {
+                for replicate in 1..=replicates {
+                    rows.push(WorksheetRow {
+                        run: rows.len() + 1,
+                        name: format!("{part} {operator} {replicate}"),
+                        part: part.clone(),
+                        operator: operator.clone(),
+                        replicate,
+                        nominal,
+                    });
+                }
+            }
+        }
+        if rows.is_empty() {
+            bail!("Worksheet requires at least one part, operator and replicate");
+        }
+        Ok(Self { seed: 0, rows })
+    }
+
+    /// CSV in run order with the import columns and `measured` blank, so the
+    /// filled-in sheet can be opened directly
+    pub fn to_csv(&self) -> Result<String> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record([
+            "name",
+            "part",
+            "operator",
+            "replicate",
+            "measured",
+            "nominal",
+        ])?;
+        for row in self.rows.iter() {
+            writer.write_record([
+                row.name.as_str(),
+                &row.part,
+                &row.operator,
+                &row.replicate.to_string(),
+                "",
+                &row.nominal.to_string(),
+            ])?;
+        }
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+
+    /// Printable HTML sheet with a run number column and an empty box per reading
+    pub fn to_html(&self, title: &str) -> String {
+        let title = escape_markup(title);
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>\n\
+             body {{ font-family: sans-serif; }}\n\
+             table {{ border-collapse: collapse; width: 100%; }}\n\
+             th, td {{ border: 1px solid #000; padding: 4px 8px; text-align: left; }}\n\
+             td.measured {{ width: 25%; }}\n\
+             </style>\n</head>\n<body>\n<h1>{title}</h1>\n\
+             <p>{} runs, randomization seed {}</p>\n<table>\n\
+             <tr><th>Run</th><th>name</th><th>part</th><th>operator</th>\
+             <th>replicate</th><th>measured</th><th>nominal</th></tr>\n",
+            self.rows.len(),
+            self.seed
+        );
+        for row in self.rows.iter() {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+                 <td class=\"measured\"></td><td>{}</td></tr>\n",
+                row.run,
+                escape_markup(&row.name),
+                escape_markup(&row.part),
+                escape_markup(&row.operator),
+                row.replicate,
+                row.nominal
+            ));
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{Design, Worksheet};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// "New study from plan": builds a randomized worksheet from part and operator
+/// IDs and saves it as a re-importable CSV or a printable sheet.
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct WorksheetGenerator {
+    /// Part IDs, one per line or comma separated
+    pub parts: String,
+    /// Operator IDs, one per line or comma separated
+    pub operators: String,
+    pub replicates: usize,
+    pub nominal: f64,
+    pub use_seed: bool,
+    pub seed: u64,
+    #[serde(skip)]
+    worksheet: Option<Worksheet>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl Default for WorksheetGenerator {
+    fn default() -> Self {
+        let design = Design::new(10, 3, 2);
+        Self {
+            parts: design.part_ids().join("\n"),
+            operators: design.operator_ids().join("\n"),
+            replicates: design.replicates,
+            nominal: 0.0,
+            use_seed: false,
+            seed: 1,
+            worksheet: None,
+            error: None,
+        }
+    }
+}
+
+impl WorksheetGenerator {
+    pub fn name(&self) -> &'static str {
+        "☰ New Study from Plan"
+    }
+
+    /// `design` is the planner's chosen design, offered as a starting point.
+    /// Returns the file name and content of a sheet when one is requested.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        design: Option<Design>,
+        open: &mut bool,
+    ) -> Option<(String, String)> {
+        let mut file = None;
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(true)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                file = self.ui(ui, design);
+            });
+        file
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, design: Option<Design>) -> Option<(String, String)> {
+        if let Some(design) = design {
+            if ui
+                .button(format!("Use planner design {}", design.label()))
+                .clicked()
+            {
+                self.parts = design.part_ids().join("\n");
+                self.operators = design.operator_ids().join("\n");
+                self.replicates = design.replicates;
+                self.worksheet = None;
+            }
+        }
+        ui.columns(2, |columns| {
+            columns[0].strong("Part IDs");
+            columns[0].add(
+                egui::TextEdit::multiline(&mut self.parts)
+                    .desired_rows(8)
+                    .desired_width(f32::INFINITY),
+            );
+            columns[1].strong("Operator IDs");
+            columns[1].add(
+                egui::TextEdit::multiline(&mut self.operators)
+                    .desired_rows(8)
+                    .desired_width(f32::INFINITY),
+            );
+        });
+        egui::Grid::new("worksheet_settings").show(ui, |ui| {
+            ui.label("Replicates: ");
+            ui.add(egui::DragValue::new(&mut self.replicates).range(1..=20));
+            ui.end_row();
+            ui.label("Nominal: ");
+            ui.add(egui::DragValue::new(&mut self.nominal).speed(0.1));
+            ui.end_row();
+            ui.checkbox(&mut self.use_seed, "Seed: ");
+            ui.add_enabled(self.use_seed, egui::DragValue::new(&mut self.seed));
+            ui.end_row();
+        });
+        if ui.button("Generate run order").clicked() {
+            // Without a fixed seed use the frame time, which differs between clicks
+            let seed = if self.use_seed {
+                self.seed
+            } else {
+                ui.ctx().input(|i| i.time).to_bits()
+            };
+            self.generate(seed);
+        }
+        if let Some(err) = &self.error {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+        let mut file = None;
+        if let Some(worksheet) = &self.worksheet {
+            ui.separator();
+            ui.label(format!(
+                "{} runs, seed {}",
+                worksheet.rows.len(),
+                worksheet.seed
+            ));
+            ui.horizontal(|ui| {
+                if ui.button("Save CSV...").clicked() {
+                    match worksheet.to_csv() {
+                        Ok(csv) => file = Some(("gage_study_worksheet.csv".to_owned(), csv)),
+                        Err(e) => tracing::error!("Worksheet::to_csv: {e:?}"),
+                    }
+                }
+                if ui.button("Save printable sheet...").clicked() {
+                    file = Some((
+                        "gage_study_worksheet.html".to_owned(),
+                        worksheet.to_html("Gage Study Worksheet"),
+                    ));
+                }
+            });
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.style_mut().override_text_style = Some(egui::style::TextStyle::Monospace);
+                egui::Grid::new("worksheet_rows")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for title in ["Run", "Part", "Operator", "Replicate"] {
+                            ui.strong(title);
+                        }
+                        ui.end_row();
+                        for row in worksheet.rows.iter() {
+                            ui.label(format!("{:>4}", row.run));
+                            ui.label(row.part.as_str());
+                            ui.label(row.operator.as_str());
+                            ui.label(format!("{:>3}", row.replicate));
+                            ui.end_row();
+                        }
+                    });
+            });
+        }
+        file
+    }
+
+    /// The most recently generated run order
+    pub fn worksheet(&self) -> Option<&Worksheet> {
+        self.worksheet.as_ref()
+    }
+
+    fn generate(&mut self, seed: u64) {
+        let parts = split_ids(&self.parts);
+        let operators = split_ids(&self.operators);
+        match Worksheet::from_plan(&parts, &operators, self.replicates, self.nominal, seed) {
+            Ok(worksheet) => {
+                self.worksheet = Some(worksheet);
+                self.error = None;
+            }
+            Err(e) => {
+                tracing::error!("Worksheet::from_plan: {e:?}");
+                self.worksheet = None;
+                self.error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+fn split_ids(text: &str) -> Vec<String> {
+    text.split(['\n', ','])
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_owned)
+        .collect()
+}